@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address;
+use crate::state::*;
+use crate::errors::*;
+
+/// Load and check one bundled coupon from `remaining_accounts`
+///
+/// - CouponData must be a program account belonging to `merchant_info`
+/// - Merchant must not be suspended (resales are frozen while suspended)
+/// - Coupon must still be tradeable (active, unexpired, uses left)
+pub fn load_tradeable_coupon(
+    coupon_info: &AccountInfo,
+    merchant_info: &AccountInfo,
+    now: i64,
+) -> Result<CouponData> {
+    require_keys_eq!(*coupon_info.owner, crate::ID, CouponError::InvalidAccountType);
+    require_keys_eq!(*merchant_info.owner, crate::ID, CouponError::InvalidAccountType);
+
+    let coupon_data = CouponData::try_deserialize(&mut &coupon_info.try_borrow_data()?[..])?;
+    let merchant = Merchant::try_deserialize(&mut &merchant_info.try_borrow_data()?[..])?;
+    require_keys_eq!(
+        coupon_data.merchant,
+        merchant_info.key(),
+        CouponError::UnauthorizedMerchant
+    );
+    require!(!merchant.is_suspended, CouponError::MerchantSuspended);
+    coupon_data.require_tradeable(now)?;
+
+    Ok(coupon_data)
+}
+
+/// Ensure `escrow_info` is the bundle PDA's associated token account for `mint`
+pub fn require_bundle_escrow(escrow_info: &AccountInfo, bundle: &Pubkey, mint: &Pubkey) -> Result<()> {
+    require_keys_eq!(
+        escrow_info.key(),
+        get_associated_token_address(bundle, mint),
+        CouponError::BundleMismatch
+    );
+    Ok(())
+}
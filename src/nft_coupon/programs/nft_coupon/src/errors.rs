@@ -14,6 +14,9 @@ pub enum CouponError {
     #[msg("Invalid discount percentage (must be 1-100)")]
     InvalidDiscountPercentage,
 
+    #[msg("Invalid discount (bad amount, currency code or SKU hash)")]
+    InvalidDiscount,
+
     #[msg("Expiry date must be in the future")]
     InvalidExpiryDate,
 
@@ -23,9 +26,30 @@ pub enum CouponError {
     #[msg("Unauthorized: only the coupon owner can perform this action")]
     UnauthorizedOwner,
 
+    #[msg("Unauthorized: only the platform admin can perform this action")]
+    UnauthorizedAdmin,
+
+    #[msg("Merchant is not verified by the platform")]
+    MerchantNotVerified,
+
+    #[msg("Merchant is suspended - all of its coupons are frozen")]
+    MerchantSuspended,
+
     #[msg("Business name is too long (max 100 characters)")]
     BusinessNameTooLong,
 
+    #[msg("URI is too long (max 200 characters)")]
+    UriTooLong,
+
+    #[msg("Signer is not the pending merchant authority")]
+    NotPendingAuthority,
+
+    #[msg("Coupon title is too long (max 64 bytes)")]
+    TitleTooLong,
+
+    #[msg("Coupon description is too long (max 256 bytes)")]
+    DescriptionTooLong,
+
     #[msg("Invalid redemption amount")]
     InvalidRedemptionAmount,
 
@@ -52,4 +76,157 @@ pub enum CouponError {
 
     #[msg("Invalid NFT amount - seller must own exactly 1 NFT")]
     InvalidNFTAmount,
+
+    #[msg("Account is already at the current layout version")]
+    AlreadyMigrated,
+
+    #[msg("Account must be migrated to the current layout version first")]
+    MigrationRequired,
+
+    #[msg("Merchant still has open coupons - close them first")]
+    MerchantHasOpenCoupons,
+
+    #[msg("Coupon cannot be closed while it is live in a holder's wallet")]
+    CouponStillLive,
+
+    #[msg("Account is not a valid account of the expected type")]
+    InvalidAccountType,
+
+    #[msg("No accounts provided for batch operation")]
+    EmptyBatch,
+
+    #[msg("Caller is not on this coupon's allowlist")]
+    NotOnAllowlist,
+
+    #[msg("Allowlist quota exhausted for this wallet")]
+    AllowlistQuotaExceeded,
+
+    #[msg("Allowlist receipt account is required for quota-limited wallets")]
+    MissingAllowlistReceipt,
+
+    #[msg("Token gate not satisfied: caller does not hold the required token or collection NFT")]
+    TokenGateNotSatisfied,
+
+    #[msg("Token gate accounts missing from remaining accounts")]
+    MissingGateAccounts,
+
+    #[msg("This coupon is not on sale yet")]
+    SaleNotStarted,
+
+    #[msg("The sale window for this coupon has ended")]
+    SaleEnded,
+
+    #[msg("Redemption is not open yet for this coupon")]
+    RedemptionNotOpen,
+
+    #[msg("The redemption window for this coupon has closed")]
+    RedemptionClosed,
+
+    #[msg("Invalid time window (start must be before end, end no later than expiry)")]
+    InvalidTimeWindow,
+
+    #[msg("Coupon can only be redeemed during its scheduled hours")]
+    OutsideRedemptionHours,
+
+    #[msg("Invalid redemption schedule (weekday mask, minutes or UTC offset out of range)")]
+    InvalidRedemptionSchedule,
+
+    #[msg("Redemption cooldown still active - try again later")]
+    RedemptionCooldownActive,
+
+    #[msg("Invalid redemption cooldown (must not be negative)")]
+    InvalidCooldown,
+
+    #[msg("This coupon is not a stored-value coupon")]
+    NotStoredValueCoupon,
+
+    #[msg("Stored-value coupons must be redeemed with redeem_amount")]
+    UseRedeemAmount,
+
+    #[msg("Redeem amount exceeds the remaining balance")]
+    InsufficientBalance,
+
+    #[msg("Invalid holdback period (must not be negative)")]
+    InvalidHoldback,
+
+    #[msg("Merchant uses deferred settlement - revenue vault account required")]
+    MissingRevenueVault,
+
+    #[msg("Coupon still has escrowed revenue - withdraw or refund it first")]
+    RevenueStillEscrowed,
+
+    #[msg("No escrowed revenue is ready to withdraw")]
+    NothingToWithdraw,
+
+    #[msg("Coupon is not refundable (must be cancelled, unused and have escrowed funds)")]
+    NotRefundable,
+
+    #[msg("Offer has expired")]
+    OfferExpired,
+
+    #[msg("Invalid offer expiry (must be in the future)")]
+    InvalidOfferExpiry,
+
+    #[msg("Coupon does not match the collection offer's merchant or terms")]
+    OfferTermsNotMet,
+
+    #[msg("Invalid auction (end must be in the future and before coupon expiry, increment > 0)")]
+    InvalidAuction,
+
+    #[msg("Auction has ended")]
+    AuctionEnded,
+
+    #[msg("Auction has not ended yet")]
+    AuctionNotEnded,
+
+    #[msg("Bid is below the reserve price or minimum increment")]
+    BidTooLow,
+
+    #[msg("Previous top bidder account missing or incorrect")]
+    InvalidPreviousBidder,
+
+    #[msg("NFT recipient does not match the auction outcome")]
+    InvalidAuctionRecipient,
+
+    #[msg("Invalid price curve (start >= end > 0, start time before end time, step >= 0)")]
+    InvalidPriceCurve,
+
+    #[msg("Current price exceeds the buyer's maximum price")]
+    PriceExceedsMaximum,
+
+    #[msg("Invalid listing expiry (must be in the future and no later than coupon expiry)")]
+    InvalidListingExpiry,
+
+    #[msg("Resale listing has expired")]
+    ListingExpired,
+
+    #[msg("Resale listing has not expired yet")]
+    ListingNotExpired,
+
+    #[msg("Invalid bundle (2 to 8 coupons, 4 accounts per coupon)")]
+    InvalidBundleSize,
+
+    #[msg("Bundle accounts don't match the listed coupons")]
+    BundleMismatch,
+
+    #[msg("Coupon has an unsettled auction")]
+    AuctionInProgress,
+
+    #[msg("Coupon was cancelled with a refund owed and can't be reactivated")]
+    CouponCancelled,
+
+    #[msg("Platform wallet doesn't match the platform config's fee wallet")]
+    InvalidFeeWallet,
+
+    #[msg("Invalid token gate (a gate needs a mint, no gate must not have one, token gates need a min amount)")]
+    InvalidGateConfig,
+
+    #[msg("Token gate can't change after the coupon has left escrow")]
+    GateLocked,
+
+    #[msg("Cancelling a sold coupon needs the merchant's revenue vault to hold the refund")]
+    RefundVaultRequired,
+
+    #[msg("Coupons restricted to an allowlist or token gate can't be auctioned")]
+    RestrictedCouponAuction,
 }
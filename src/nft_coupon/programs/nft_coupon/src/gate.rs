@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, TokenAccount};
+use mpl_token_metadata::accounts::Metadata;
+use crate::state::*;
+use crate::errors::*;
+
+/// Enforce the coupon's token gate (if any) for `wallet`
+///
+/// Gate accounts are passed via `remaining_accounts`:
+/// - TokenBalance: [wallet's token account for gate_mint]
+/// - Collection:   [wallet's token account for the held NFT, that NFT's metadata account]
+pub fn require_token_gate(
+    coupon_data: &CouponData,
+    wallet: &Pubkey,
+    remaining_accounts: &[AccountInfo],
+) -> Result<()> {
+    if coupon_data.gate_kind == GateKind::None {
+        return Ok(());
+    }
+
+    let token_info = remaining_accounts
+        .first()
+        .ok_or(CouponError::MissingGateAccounts)?;
+    require_keys_eq!(*token_info.owner, token::ID, CouponError::TokenGateNotSatisfied);
+    let holding = TokenAccount::try_deserialize(&mut &token_info.data.borrow()[..])?;
+
+    // Holding must belong to the caller and meet the minimum (at least 1 for NFTs)
+    require_keys_eq!(holding.owner, *wallet, CouponError::TokenGateNotSatisfied);
+    require!(
+        holding.amount >= coupon_data.gate_min_amount.max(1),
+        CouponError::TokenGateNotSatisfied
+    );
+
+    match coupon_data.gate_kind {
+        GateKind::TokenBalance => {
+            require_keys_eq!(
+                holding.mint,
+                coupon_data.gate_mint,
+                CouponError::TokenGateNotSatisfied
+            );
+        }
+        GateKind::Collection => {
+            // Metadata must be the canonical Metaplex PDA for the held mint
+            let metadata_info = remaining_accounts
+                .get(1)
+                .ok_or(CouponError::MissingGateAccounts)?;
+            let (metadata_pda, _) = Metadata::find_pda(&holding.mint);
+            require_keys_eq!(metadata_info.key(), metadata_pda, CouponError::TokenGateNotSatisfied);
+            require_keys_eq!(
+                *metadata_info.owner,
+                mpl_token_metadata::ID,
+                CouponError::TokenGateNotSatisfied
+            );
+
+            // Only a verified collection counts (unverified can be set by anyone)
+            let metadata = Metadata::from_bytes(&metadata_info.data.borrow())
+                .map_err(|_| CouponError::TokenGateNotSatisfied)?;
+            let in_collection = metadata
+                .collection
+                .is_some_and(|c| c.verified && c.key == coupon_data.gate_mint);
+            require!(in_collection, CouponError::TokenGateNotSatisfied);
+        }
+        GateKind::None => {}
+    }
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+
+/// Step 2 of merchant authority rotation
+///
+/// The proposed wallet signs to take control. The merchant PDA address
+/// stays the same because it is derived from `seed_authority`.
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        constraint = merchant.pending_authority == Some(new_authority.key()) @ CouponError::NotPendingAuthority
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    pub new_authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<AcceptAuthority>) -> Result<()> {
+    let merchant = &mut ctx.accounts.merchant;
+    let previous = merchant.authority;
+
+    merchant.authority = ctx.accounts.new_authority.key();
+    merchant.pending_authority = None;
+
+    msg!(
+        "Merchant {} authority rotated: {} -> {}",
+        merchant.key(),
+        previous,
+        merchant.authority
+    );
+
+    Ok(())
+}
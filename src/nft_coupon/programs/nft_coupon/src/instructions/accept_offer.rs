@@ -0,0 +1,158 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount, Transfer},
+};
+use crate::state::*;
+use crate::errors::*;
+
+/// Accept an offer on a coupon NFT you hold
+///
+/// Atomically:
+/// 1. NFT transferred from the holder to the bidder
+/// 2. Offered SOL split from the Offer PDA (97.5% to holder, 2.5% to platform)
+/// 3. Offer PDA closed, rent returned to the bidder
+#[derive(Accounts)]
+pub struct AcceptOffer<'info> {
+    /// NFT mint account
+    pub nft_mint: Account<'info, Mint>,
+
+    /// Coupon data account (PDA derived from NFT mint address)
+    /// Links the traded NFT to its merchant
+    #[account(
+        seeds = [b"coupon", nft_mint.key().as_ref()],
+        bump = coupon_data.bump,
+        has_one = merchant @ CouponError::UnauthorizedMerchant,
+    )]
+    pub coupon_data: Account<'info, CouponData>,
+
+    /// Merchant account - trading is blocked while the merchant is suspended
+    #[account(
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        constraint = !merchant.is_suspended @ CouponError::MerchantSuspended,
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    /// Offer PDA - closed, rent returned to bidder
+    /// Seeds: ["offer", nft_mint, bidder]
+    #[account(
+        mut,
+        close = bidder,
+        seeds = [b"offer", nft_mint.key().as_ref(), bidder.key().as_ref()],
+        bump = offer.bump,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    /// Seller's token account (source) - must hold the NFT
+    #[account(
+        mut,
+        token::mint = nft_mint,
+        token::authority = seller,
+        constraint = seller_token_account.amount == 1 @ CouponError::InvalidNFTAmount,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    /// Bidder's token account (destination) - created if not exists
+    #[account(
+        init_if_needed,
+        payer = seller,
+        associated_token::mint = nft_mint,
+        associated_token::authority = bidder,
+    )]
+    pub bidder_token_account: Account<'info, TokenAccount>,
+
+    /// Bidder - receives the NFT and the offer account rent
+    /// CHECK: Validated by offer PDA seeds
+    #[account(mut)]
+    pub bidder: UncheckedAccount<'info>,
+
+    /// Seller - current holder, receives 97.5% of the offer
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// Platform config singleton (fee wallet)
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// Platform fee wallet - receives 2.5% of the offer
+    /// CHECK: Must be the fee wallet stored in the platform config
+    #[account(
+        mut,
+        address = platform_config.fee_wallet @ CouponError::InvalidFeeWallet,
+    )]
+    pub platform_wallet: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<AcceptOffer>) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(
+        ctx.accounts.offer.expires_at > current_time,
+        CouponError::OfferExpired
+    );
+    ctx.accounts.coupon_data.require_tradeable(current_time)?;
+
+    // Calculate payment splits
+    let price_lamports = ctx.accounts.offer.amount;
+    let platform_fee = (price_lamports
+        .checked_mul(25)
+        .ok_or(CouponError::ArithmeticOverflow)?)
+        .checked_div(1000)
+        .ok_or(CouponError::ArithmeticOverflow)?; // 2.5%
+    let seller_amount = price_lamports
+        .checked_sub(platform_fee)
+        .ok_or(CouponError::ArithmeticOverflow)?; // 97.5%
+
+    // ATOMIC TRANSACTION STEP 1: Transfer NFT from seller to bidder
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.seller_token_account.to_account_info(),
+        to: ctx.accounts.bidder_token_account.to_account_info(),
+        authority: ctx.accounts.seller.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token::transfer(CpiContext::new(cpi_program, cpi_accounts), 1)?;
+
+    // ATOMIC TRANSACTION STEP 2: Pay out the locked offer (Offer PDA is program-owned)
+    ctx.accounts.offer.sub_lamports(price_lamports)?;
+    ctx.accounts.seller.add_lamports(seller_amount)?;
+    if platform_fee > 0 {
+        ctx.accounts.platform_wallet.add_lamports(platform_fee)?;
+    }
+
+    msg!(
+        "🎉 Offer accepted! NFT {} | Seller: {} | Bidder: {} | Price: {} lamports | Fee: {}",
+        ctx.accounts.nft_mint.key(),
+        ctx.accounts.seller.key(),
+        ctx.accounts.bidder.key(),
+        price_lamports,
+        platform_fee
+    );
+
+    emit!(OfferAcceptedEvent {
+        nft_mint: ctx.accounts.nft_mint.key(),
+        seller: ctx.accounts.seller.key(),
+        bidder: ctx.accounts.bidder.key(),
+        price: price_lamports,
+        platform_fee,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct OfferAcceptedEvent {
+    pub nft_mint: Pubkey,
+    pub seller: Pubkey,
+    pub bidder: Pubkey,
+    pub price: u64,
+    pub platform_fee: u64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+
+/// Deactivate/reactivate many coupons in one transaction
+///
+/// CouponData accounts (writable) are passed via `remaining_accounts`.
+/// Every coupon must belong to the signing merchant, otherwise the
+/// whole batch fails. Refunds for sold coupons cancelled here are
+/// escrowed as in update_coupon_status.
+#[derive(Accounts)]
+pub struct BatchUpdateCouponStatus<'info> {
+    #[account(
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        has_one = authority @ CouponError::UnauthorizedMerchant
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    /// Merchant revenue vault - only needed to cancel sold coupons
+    /// Seeds: ["revenue_vault", merchant]
+    #[account(
+        mut,
+        seeds = [b"revenue_vault", merchant.key().as_ref()],
+        bump = revenue_vault.bump,
+    )]
+    pub revenue_vault: Option<Account<'info, RevenueVault>>,
+
+    /// Pays any refunds not already escrowed
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<BatchUpdateCouponStatus>,
+    is_active: bool,
+) -> Result<()> {
+    require!(
+        !ctx.remaining_accounts.is_empty(),
+        CouponError::EmptyBatch
+    );
+
+    let merchant_key = ctx.accounts.merchant.key();
+    let mut refund_shortfall: u64 = 0;
+
+    for coupon_info in ctx.remaining_accounts.iter() {
+        require_keys_eq!(*coupon_info.owner, crate::ID, CouponError::InvalidAccountType);
+        require!(coupon_info.is_writable, CouponError::InvalidAccountType);
+
+        let mut data = coupon_info.try_borrow_mut_data()?;
+        let mut coupon_data = CouponData::try_deserialize(&mut &data[..])?;
+        require_keys_eq!(
+            coupon_data.merchant,
+            merchant_key,
+            CouponError::UnauthorizedMerchant
+        );
+
+        refund_shortfall = refund_shortfall
+            .checked_add(coupon_data.set_active(is_active)?)
+            .ok_or(CouponError::ArithmeticOverflow)?;
+        coupon_data.try_serialize(&mut &mut data[..])?;
+    }
+
+    super::update_coupon_status::escrow_refunds(
+        ctx.accounts.revenue_vault.as_mut(),
+        &ctx.accounts.authority,
+        refund_shortfall,
+    )?;
+
+    let count = ctx.remaining_accounts.len() as u32;
+
+    msg!(
+        "Batch status update: {} coupons {}",
+        count,
+        if is_active { "activated" } else { "deactivated" }
+    );
+
+    // One summary event for the whole batch (indexers re-read the coupons)
+    emit!(BatchCouponStatusEvent {
+        merchant: merchant_key,
+        is_active,
+        count,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct BatchCouponStatusEvent {
+    pub merchant: Pubkey,
+    pub is_active: bool,
+    pub count: u32,
+    pub timestamp: i64,
+}
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Token, TokenAccount, Transfer};
+use crate::state::*;
+use crate::errors::*;
+use crate::bundle;
+
+/// Delist a bundle and return every NFT to the seller
+///
+/// Per coupon, `remaining_accounts` holds (in the listed order):
+/// 1. Bundle PDA's associated token account for the mint (writable)
+/// 2. Seller's token account for the mint (writable)
+#[derive(Accounts)]
+pub struct CancelBundle<'info> {
+    /// Bundle listing PDA - closed, rent returned to seller
+    /// Seeds: ["bundle", seller, bundle_id]
+    #[account(
+        mut,
+        close = seller,
+        seeds = [
+            b"bundle",
+            seller.key().as_ref(),
+            &bundle_listing.bundle_id.to_le_bytes(),
+        ],
+        bump = bundle_listing.bump,
+    )]
+    pub bundle_listing: Account<'info, BundleListing>,
+
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, CancelBundle<'info>>) -> Result<()> {
+    let bundle_listing = &ctx.accounts.bundle_listing;
+    let groups = ctx.remaining_accounts.chunks_exact(2);
+    require!(
+        groups.remainder().is_empty() && groups.len() == bundle_listing.mints.len(),
+        CouponError::BundleMismatch
+    );
+
+    let bundle_key = bundle_listing.key();
+    let seller_key = ctx.accounts.seller.key();
+    let bundle_id_bytes = bundle_listing.bundle_id.to_le_bytes();
+    let bump = bundle_listing.bump;
+    let seeds = &[
+        b"bundle".as_ref(),
+        seller_key.as_ref(),
+        bundle_id_bytes.as_ref(),
+        &[bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    for (group, mint) in groups.zip(bundle_listing.mints.iter()) {
+        bundle::require_bundle_escrow(&group[0], &bundle_key, mint)?;
+
+        let seller_token_account = Account::<TokenAccount>::try_from(&group[1])?;
+        require_keys_eq!(seller_token_account.mint, *mint, CouponError::BundleMismatch);
+        require_keys_eq!(seller_token_account.owner, seller_key, CouponError::BundleMismatch);
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: group[0].clone(),
+                    to: group[1].clone(),
+                    authority: ctx.accounts.bundle_listing.to_account_info(),
+                },
+                signer,
+            ),
+            1,
+        )?;
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: group[0].clone(),
+                destination: ctx.accounts.seller.to_account_info(),
+                authority: ctx.accounts.bundle_listing.to_account_info(),
+            },
+            signer,
+        ))?;
+    }
+
+    msg!(
+        "Bundle #{} cancelled: {} NFTs returned to {}",
+        ctx.accounts.bundle_listing.bundle_id,
+        ctx.accounts.bundle_listing.mints.len(),
+        seller_key
+    );
+
+    Ok(())
+}
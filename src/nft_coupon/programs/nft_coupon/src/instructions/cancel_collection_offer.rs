@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+/// Cancel a collection offer and reclaim the locked SOL (plus rent)
+///
+/// Allowed at any time, including after expiry.
+#[derive(Accounts)]
+pub struct CancelCollectionOffer<'info> {
+    /// Collection offer PDA - closed, offered SOL + rent returned to bidder
+    /// Seeds: ["collection_offer", merchant, bidder, offer_id]
+    #[account(
+        mut,
+        close = bidder,
+        seeds = [
+            b"collection_offer",
+            collection_offer.merchant.as_ref(),
+            bidder.key().as_ref(),
+            &collection_offer.offer_id.to_le_bytes(),
+        ],
+        bump = collection_offer.bump,
+    )]
+    pub collection_offer: Account<'info, CollectionOffer>,
+
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<CancelCollectionOffer>) -> Result<()> {
+    msg!(
+        "Collection offer #{} cancelled by {}: {} lamports returned",
+        ctx.accounts.collection_offer.offer_id,
+        ctx.accounts.bidder.key(),
+        ctx.accounts.collection_offer.amount
+    );
+
+    Ok(())
+}
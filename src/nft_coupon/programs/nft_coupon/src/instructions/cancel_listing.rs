@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer},
+};
+use crate::state::*;
+
+/// Delist a coupon from resale (seller only, any time)
+///
+/// 1. NFT returned from the Resale Escrow PDA to the seller's wallet
+/// 2. Resale Escrow PDA closed, rent returned to the seller
+/// 3. Listing closed if there is one - escrows listed before ResaleListing
+///    existed have none and can be recovered without it
+#[derive(Accounts)]
+pub struct CancelListing<'info> {
+    /// NFT mint account
+    pub nft_mint: Account<'info, Mint>,
+
+    /// Resale listing PDA - closed, rent returned to seller
+    /// Seeds: ["listing", nft_mint, seller]
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"listing", nft_mint.key().as_ref(), seller.key().as_ref()],
+        bump = listing.bump,
+        has_one = seller,
+    )]
+    pub listing: Option<Account<'info, ResaleListing>>,
+
+    /// Resale Escrow PDA - holds the listed NFT
+    /// Seeds: ["resale_escrow", nft_mint, seller]
+    /// Authority: Resale Escrow PDA itself (self-custodial, can sign via PDA)
+    #[account(
+        mut,
+        seeds = [b"resale_escrow", nft_mint.key().as_ref(), seller.key().as_ref()],
+        bump,
+        token::mint = nft_mint,
+        token::authority = resale_escrow,
+    )]
+    pub resale_escrow: Account<'info, TokenAccount>,
+
+    /// Seller's token account (destination) - created if not exists
+    #[account(
+        init_if_needed,
+        payer = seller,
+        associated_token::mint = nft_mint,
+        associated_token::authority = seller,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    /// Seller - gets the NFT and the rent back
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<CancelListing>) -> Result<()> {
+    // Get PDA signer seeds for Resale Escrow
+    let nft_mint_key = ctx.accounts.nft_mint.key();
+    let seller_key = ctx.accounts.seller.key();
+    let bump = ctx.bumps.resale_escrow;
+    let seeds = &[
+        b"resale_escrow".as_ref(),
+        nft_mint_key.as_ref(),
+        seller_key.as_ref(),
+        &[bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    // 1. Return the NFT to the seller (the escrow may already be empty)
+    let amount = ctx.accounts.resale_escrow.amount;
+    if amount > 0 {
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.resale_escrow.to_account_info(),
+                    to: ctx.accounts.seller_token_account.to_account_info(),
+                    authority: ctx.accounts.resale_escrow.to_account_info(),
+                },
+                signer,
+            ),
+            amount,
+        )?;
+    }
+
+    // 2. Close the (now empty) Resale Escrow PDA
+    token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.resale_escrow.to_account_info(),
+            destination: ctx.accounts.seller.to_account_info(),
+            authority: ctx.accounts.resale_escrow.to_account_info(),
+        },
+        signer,
+    ))?;
+
+    msg!(
+        "Listing for {} cancelled: NFT returned to {}{}",
+        nft_mint_key,
+        seller_key,
+        if ctx.accounts.listing.is_some() { "" } else { " (no listing account)" }
+    );
+
+    Ok(())
+}
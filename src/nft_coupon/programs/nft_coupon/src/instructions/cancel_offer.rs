@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+/// Cancel an offer and reclaim the locked SOL (plus rent)
+///
+/// Allowed at any time, including after expiry.
+#[derive(Accounts)]
+pub struct CancelOffer<'info> {
+    /// Offer PDA - closed, offered SOL + rent returned to bidder
+    /// Seeds: ["offer", nft_mint, bidder]
+    #[account(
+        mut,
+        close = bidder,
+        seeds = [b"offer", offer.mint.as_ref(), bidder.key().as_ref()],
+        bump = offer.bump,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<CancelOffer>) -> Result<()> {
+    msg!(
+        "Offer on {} cancelled by {}: {} lamports returned",
+        ctx.accounts.offer.mint,
+        ctx.accounts.bidder.key(),
+        ctx.accounts.offer.amount
+    );
+
+    Ok(())
+}
@@ -5,6 +5,7 @@ use anchor_spl::{
 };
 use crate::state::*;
 use crate::errors::*;
+use crate::{gate, merkle};
 
 /// Claim a free coupon NFT from the Escrow PDA
 ///
@@ -29,10 +30,12 @@ pub struct ClaimCoupon<'info> {
     pub coupon_data: Account<'info, CouponData>,
 
     /// Merchant account (PDA derived from merchant authority)
+    /// Claims/purchases are blocked while the merchant is suspended
     #[account(
         mut,
-        seeds = [b"merchant", merchant.authority.as_ref()],
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
         bump,
+        constraint = !merchant.is_suspended @ CouponError::MerchantSuspended,
     )]
     pub merchant: Account<'info, Merchant>,
 
@@ -65,12 +68,35 @@ pub struct ClaimCoupon<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
+    /// Allowlist receipt - only required when the caller's allowlist leaf has a quota
+    /// Seeds: ["allowlist_receipt", allowlist_root, user]
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = AllowlistReceipt::LEN,
+        seeds = [b"allowlist_receipt", coupon_data.allowlist_root.as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub allowlist_receipt: Option<Account<'info, AllowlistReceipt>>,
+
+    /// Redeemer record for unique redeemer stats - paid here by the user so
+    /// redeeming later costs no rent
+    /// Seeds: ["redeemer", merchant, user]
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = RedeemerRecord::LEN,
+        seeds = [b"redeemer", merchant.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub redeemer_record: Account<'info, RedeemerRecord>,
+
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<ClaimCoupon>) -> Result<()> {
+pub fn handler(ctx: Context<ClaimCoupon>, proof: Option<AllowlistProof>) -> Result<()> {
     let coupon_data = &ctx.accounts.coupon_data;
 
     // Validate expiry date
@@ -80,14 +106,38 @@ pub fn handler(ctx: Context<ClaimCoupon>) -> Result<()> {
         CouponError::CouponExpired
     );
 
+    // Validate sale window (scheduled drops)
+    coupon_data.require_on_sale(current_time)?;
+
     // Validate redemptions remaining
     require!(
         coupon_data.redemptions_remaining > 0,
         CouponError::NoRedemptionsRemaining
     );
 
+    // Validate allowlist membership (VIP-only drops)
+    merkle::require_allowlisted(
+        coupon_data,
+        &ctx.accounts.user.key(),
+        proof.as_ref(),
+        ctx.accounts.allowlist_receipt.as_deref_mut(),
+    )?;
+    if let (Some(receipt), Some(bump)) = (
+        ctx.accounts.allowlist_receipt.as_mut(),
+        ctx.bumps.allowlist_receipt,
+    ) {
+        receipt.bump = bump;
+    }
+
+    // Validate token gate (partner collection / DEAL-token holders)
+    gate::require_token_gate(
+        coupon_data,
+        &ctx.accounts.user.key(),
+        ctx.remaining_accounts,
+    )?;
+
     // Get PDA signer seeds for merchant (the escrow's authority)
-    let authority_key = ctx.accounts.merchant.authority.key();
+    let authority_key = ctx.accounts.merchant.seed_authority.key();
     let merchant_bump = ctx.bumps.merchant;
     let merchant_seeds = &[
         b"merchant".as_ref(),
@@ -115,6 +165,14 @@ pub fn handler(ctx: Context<ClaimCoupon>) -> Result<()> {
         .checked_sub(1)
         .ok_or(CouponError::ArithmeticOverflow)?;
 
+    ctx.accounts.merchant.record_claim()?;
+    let merchant_key = ctx.accounts.merchant.key();
+    ctx.accounts.redeemer_record.init(
+        ctx.accounts.user.key(),
+        merchant_key,
+        ctx.bumps.redeemer_record,
+    );
+
     msg!(
         "Free coupon claimed! NFT {} transferred to user {}",
         ctx.accounts.nft_mint.key(),
@@ -0,0 +1,130 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, CloseAccount, Mint, Token, TokenAccount};
+use crate::state::*;
+use crate::errors::*;
+
+/// Close a coupon and reclaim its rent
+///
+/// Allowed when the coupon can no longer be used by anyone:
+/// - Never sold: NFT still in the Escrow PDA (it is burned here)
+/// - Expired
+/// - Fully redeemed: NFT already burned (mint supply is 0)
+///
+/// Refused while an auction for the coupon is still unsettled.
+///
+/// Decrements `merchant.open_coupons` so the merchant can eventually close.
+#[derive(Accounts)]
+pub struct CloseCoupon<'info> {
+    #[account(
+        mut,
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        has_one = authority @ CouponError::UnauthorizedMerchant,
+        constraint = merchant.version == Merchant::CURRENT_VERSION @ CouponError::MigrationRequired,
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    /// Coupon data account - closed, rent returned to merchant authority
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"coupon", nft_mint.key().as_ref()],
+        bump = coupon_data.bump,
+        has_one = merchant @ CouponError::UnauthorizedMerchant,
+        constraint = coupon_data.escrowed_lamports == 0 @ CouponError::RevenueStillEscrowed,
+    )]
+    pub coupon_data: Account<'info, CouponData>,
+
+    /// NFT mint account (mut for burning an unsold NFT)
+    #[account(mut)]
+    pub nft_mint: Account<'info, Mint>,
+
+    /// NFT Escrow PDA created by create_coupon - closed, rent returned to merchant authority
+    /// Seeds: ["nft_escrow", merchant_pda, nft_mint]
+    #[account(
+        mut,
+        seeds = [b"nft_escrow", merchant.key().as_ref(), nft_mint.key().as_ref()],
+        bump,
+        token::mint = nft_mint,
+        token::authority = merchant,
+    )]
+    pub nft_escrow: Account<'info, TokenAccount>,
+
+    /// Auction PDA for this coupon - must not exist, since settling needs the coupon data
+    /// Seeds: ["auction", nft_mint]
+    /// CHECK: Only checked for being empty
+    #[account(
+        seeds = [b"auction", nft_mint.key().as_ref()],
+        bump,
+        constraint = auction.data_is_empty() @ CouponError::AuctionInProgress,
+    )]
+    pub auction: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<CloseCoupon>) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let unsold = ctx.accounts.nft_escrow.amount == 1;
+    let expired = ctx.accounts.coupon_data.expiry_date <= current_time;
+    let fully_redeemed = ctx.accounts.nft_mint.supply == 0;
+
+    require!(
+        unsold || expired || fully_redeemed,
+        CouponError::CouponStillLive
+    );
+
+    // Get PDA signer seeds for merchant (the escrow's authority)
+    let authority_key = ctx.accounts.merchant.seed_authority.key();
+    let merchant_bump = ctx.accounts.merchant.bump;
+    let merchant_seeds = &[
+        b"merchant".as_ref(),
+        authority_key.as_ref(),
+        &[merchant_bump],
+    ];
+    let signer = &[&merchant_seeds[..]];
+
+    // Burn the unsold NFT so the escrow can be closed
+    if unsold {
+        token::burn(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.nft_mint.to_account_info(),
+                    from: ctx.accounts.nft_escrow.to_account_info(),
+                    authority: ctx.accounts.merchant.to_account_info(),
+                },
+                signer,
+            ),
+            1,
+        )?;
+    }
+
+    // Close the (now empty) escrow token account
+    token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.nft_escrow.to_account_info(),
+            destination: ctx.accounts.authority.to_account_info(),
+            authority: ctx.accounts.merchant.to_account_info(),
+        },
+        signer,
+    ))?;
+
+    let merchant = &mut ctx.accounts.merchant;
+    merchant.open_coupons = merchant
+        .open_coupons
+        .checked_sub(1)
+        .ok_or(CouponError::ArithmeticOverflow)?;
+
+    msg!(
+        "Coupon {} closed ({} open coupons remaining)",
+        ctx.accounts.nft_mint.key(),
+        merchant.open_coupons
+    );
+
+    Ok(())
+}
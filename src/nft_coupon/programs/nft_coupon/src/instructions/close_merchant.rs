@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+
+/// Close a merchant account when the business leaves the platform
+///
+/// Every coupon must be closed first (open_coupons == 0).
+/// The merchant's RevenueVault (if any) must hold no escrowed proceeds and is
+/// closed too, so nothing is stranded once the merchant is gone.
+/// Rent and any leftover vault lamports are returned to the merchant authority.
+#[derive(Accounts)]
+pub struct CloseMerchant<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        has_one = authority @ CouponError::UnauthorizedMerchant,
+        constraint = merchant.version == Merchant::CURRENT_VERSION @ CouponError::MigrationRequired,
+        constraint = merchant.open_coupons == 0 @ CouponError::MerchantHasOpenCoupons,
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    /// Merchant revenue vault - only exists if deferred settlement was ever configured
+    /// Seeds: ["revenue_vault", merchant]
+    /// CHECK: PDA verified by seeds; deserialized in the handler when it exists
+    #[account(
+        mut,
+        seeds = [b"revenue_vault", merchant.key().as_ref()],
+        bump,
+    )]
+    pub revenue_vault: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<CloseMerchant>) -> Result<()> {
+    let vault_info = ctx.accounts.revenue_vault.to_account_info();
+    if !vault_info.data_is_empty() {
+        let revenue_vault = RevenueVault::try_deserialize(&mut &vault_info.try_borrow_data()?[..])?;
+        require!(
+            revenue_vault.escrowed_total == 0,
+            CouponError::RevenueStillEscrowed
+        );
+
+        // Close the vault: all lamports to the authority, account back to the system program
+        let lamports = vault_info.lamports();
+        vault_info.sub_lamports(lamports)?;
+        ctx.accounts.authority.add_lamports(lamports)?;
+        vault_info.assign(&System::id());
+        vault_info.resize(0)?;
+    }
+
+    msg!(
+        "Merchant closed: {} ({} coupons created)",
+        ctx.accounts.merchant.business_name,
+        ctx.accounts.merchant.total_coupons_created
+    );
+
+    Ok(())
+}
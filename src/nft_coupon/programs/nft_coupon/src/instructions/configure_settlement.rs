@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+
+/// Turn deferred settlement on/off for a merchant's primary sales
+///
+/// When on, purchase_coupon sends the full price to the merchant's
+/// RevenueVault (created here on first use) instead of paying out directly.
+/// Proceeds are released on redemption or after `holdback_seconds`.
+#[derive(Accounts)]
+pub struct ConfigureSettlement<'info> {
+    #[account(
+        mut,
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        has_one = authority @ CouponError::UnauthorizedMerchant
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    /// Merchant revenue vault
+    /// Seeds: ["revenue_vault", merchant]
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = RevenueVault::LEN,
+        seeds = [b"revenue_vault", merchant.key().as_ref()],
+        bump,
+    )]
+    pub revenue_vault: Account<'info, RevenueVault>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<ConfigureSettlement>,
+    deferred_settlement: bool,
+    holdback_seconds: i64,
+) -> Result<()> {
+    require!(holdback_seconds >= 0, CouponError::InvalidHoldback);
+
+    let revenue_vault = &mut ctx.accounts.revenue_vault;
+    revenue_vault.merchant = ctx.accounts.merchant.key();
+    revenue_vault.bump = ctx.bumps.revenue_vault;
+
+    // Coupons already sold keep the settle_after they were purchased with
+    let merchant = &mut ctx.accounts.merchant;
+    merchant.deferred_settlement = deferred_settlement;
+    merchant.holdback_seconds = holdback_seconds;
+
+    msg!(
+        "Merchant {} settlement: {} (holdback {}s)",
+        merchant.business_name,
+        if deferred_settlement { "deferred" } else { "direct" },
+        holdback_seconds
+    );
+
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer},
+};
+use crate::state::*;
+use crate::errors::*;
+
+/// Clean up an expired resale listing (permissionless)
+///
+/// 1. NFT returned from the Resale Escrow PDA to the seller's wallet
+/// 2. Resale Escrow PDA closed, rent returned to the seller
+/// 3. Listing closed - cranker earns `ResaleListing::CRANK_BOUNTY_LAMPORTS`
+///    from its rent, the remainder goes to the seller
+#[derive(Accounts)]
+pub struct CrankExpiredListing<'info> {
+    /// NFT mint account
+    pub nft_mint: Account<'info, Mint>,
+
+    /// Resale listing PDA - closed, remaining rent returned to seller
+    /// Seeds: ["listing", nft_mint, seller]
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"listing", nft_mint.key().as_ref(), seller.key().as_ref()],
+        bump = listing.bump,
+        has_one = seller,
+    )]
+    pub listing: Account<'info, ResaleListing>,
+
+    /// Resale Escrow PDA - holds the listed NFT
+    /// Seeds: ["resale_escrow", nft_mint, seller]
+    /// Authority: Resale Escrow PDA itself (self-custodial, can sign via PDA)
+    #[account(
+        mut,
+        seeds = [b"resale_escrow", nft_mint.key().as_ref(), seller.key().as_ref()],
+        bump,
+        token::mint = nft_mint,
+        token::authority = resale_escrow,
+        constraint = resale_escrow.amount == 1 @ CouponError::InvalidNFTAmount,
+    )]
+    pub resale_escrow: Account<'info, TokenAccount>,
+
+    /// Seller's token account (destination) - created if not exists
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        associated_token::mint = nft_mint,
+        associated_token::authority = seller,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    /// Seller - gets the NFT and the rent back
+    /// CHECK: Validated by listing.seller
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+
+    /// Anyone - pays for the seller's token account if needed, earns the bounty
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<CrankExpiredListing>) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(
+        current_time >= ctx.accounts.listing.expires_at,
+        CouponError::ListingNotExpired
+    );
+
+    // Get PDA signer seeds for Resale Escrow
+    let nft_mint_key = ctx.accounts.nft_mint.key();
+    let seller_key = ctx.accounts.seller.key();
+    let bump = ctx.bumps.resale_escrow;
+    let seeds = &[
+        b"resale_escrow".as_ref(),
+        nft_mint_key.as_ref(),
+        seller_key.as_ref(),
+        &[bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    // 1. Return the NFT to the seller
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.resale_escrow.to_account_info(),
+                to: ctx.accounts.seller_token_account.to_account_info(),
+                authority: ctx.accounts.resale_escrow.to_account_info(),
+            },
+            signer,
+        ),
+        1,
+    )?;
+
+    // 2. Close the (now empty) Resale Escrow PDA
+    token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.resale_escrow.to_account_info(),
+            destination: ctx.accounts.seller.to_account_info(),
+            authority: ctx.accounts.resale_escrow.to_account_info(),
+        },
+        signer,
+    ))?;
+
+    // 3. Cranker bounty out of the listing rent (the rest goes to the seller on close)
+    let bounty = ResaleListing::CRANK_BOUNTY_LAMPORTS.min(ctx.accounts.listing.get_lamports());
+    ctx.accounts.listing.sub_lamports(bounty)?;
+    ctx.accounts.cranker.add_lamports(bounty)?;
+
+    msg!(
+        "Expired listing for {} cleaned up: NFT returned to {} | Cranker {} earned {} lamports",
+        nft_mint_key,
+        seller_key,
+        ctx.accounts.cranker.key(),
+        bounty
+    );
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use crate::state::*;
+use crate::errors::*;
+
+/// Auction a held coupon NFT (resale)
+///
+/// The NFT moves from the seller's wallet to the Auction Escrow PDA until
+/// settle_auction hands it to the winner (or back to the seller without bids).
+#[derive(Accounts)]
+pub struct CreateAuction<'info> {
+    /// NFT mint account
+    pub nft_mint: Account<'info, Mint>,
+
+    /// Coupon data account (PDA derived from NFT mint address)
+    /// Links the auctioned NFT to its merchant
+    #[account(
+        seeds = [b"coupon", nft_mint.key().as_ref()],
+        bump = coupon_data.bump,
+        has_one = merchant @ CouponError::UnauthorizedMerchant,
+    )]
+    pub coupon_data: Account<'info, CouponData>,
+
+    /// Merchant account - trading is blocked while the merchant is suspended
+    #[account(
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        constraint = !merchant.is_suspended @ CouponError::MerchantSuspended,
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    /// Auction PDA
+    /// Seeds: ["auction", nft_mint]
+    #[account(
+        init,
+        payer = seller,
+        space = Auction::LEN,
+        seeds = [b"auction", nft_mint.key().as_ref()],
+        bump,
+    )]
+    pub auction: Account<'info, Auction>,
+
+    /// Auction Escrow PDA - holds the NFT while bidding is open
+    /// Seeds: ["auction_escrow", nft_mint]
+    /// Authority: Auction PDA
+    #[account(
+        init,
+        payer = seller,
+        seeds = [b"auction_escrow", nft_mint.key().as_ref()],
+        bump,
+        token::mint = nft_mint,
+        token::authority = auction,
+    )]
+    pub auction_escrow: Account<'info, TokenAccount>,
+
+    /// Seller's token account (source) - must hold the NFT
+    #[account(
+        mut,
+        token::mint = nft_mint,
+        token::authority = seller,
+        constraint = seller_token_account.amount == 1 @ CouponError::InvalidNFTAmount,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    /// Seller - pays for the auction accounts, receives proceeds
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<CreateAuction>,
+    reserve_price: u64,
+    end_time: i64,
+    min_increment: u64,
+) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(
+        end_time > current_time
+            && end_time < ctx.accounts.coupon_data.expiry_date
+            && min_increment > 0,
+        CouponError::InvalidAuction
+    );
+    ctx.accounts.coupon_data.require_tradeable(current_time)?;
+
+    // Move the NFT into the Auction Escrow PDA
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.seller_token_account.to_account_info(),
+        to: ctx.accounts.auction_escrow.to_account_info(),
+        authority: ctx.accounts.seller.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token::transfer(CpiContext::new(cpi_program, cpi_accounts), 1)?;
+
+    let auction = &mut ctx.accounts.auction;
+    auction.mint = ctx.accounts.nft_mint.key();
+    auction.merchant = ctx.accounts.merchant.key();
+    auction.seller = ctx.accounts.seller.key();
+    auction.is_primary = false;
+    auction.reserve_price = reserve_price;
+    auction.min_increment = min_increment;
+    auction.end_time = end_time;
+    auction.coupon_expiry = ctx.accounts.coupon_data.expiry_date;
+    auction.highest_bidder = Pubkey::default();
+    auction.highest_bid = 0;
+    auction.bump = ctx.bumps.auction;
+
+    msg!(
+        "✅ Auction created for {} by {}: reserve {} lamports, ends {}",
+        auction.mint,
+        auction.seller,
+        reserve_price,
+        end_time
+    );
+
+    Ok(())
+}
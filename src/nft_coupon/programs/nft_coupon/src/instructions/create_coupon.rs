@@ -11,25 +11,35 @@ use crate::state::*;
 use crate::errors::*;
 
 #[derive(Accounts)]
+#[instruction(title: String, description: String)]
 pub struct CreateCoupon<'info> {
     /// Merchant account (PDA derived from merchant authority)
-    /// Seeds: ["merchant", merchant_authority_pubkey]
+    /// Seeds: ["merchant", seed_authority_pubkey]
     /// Validates that the merchant authority owns this merchant account
     #[account(
         mut,
-        seeds = [b"merchant", merchant_authority.key().as_ref()],
-        bump,
-        has_one = authority @ CouponError::UnauthorizedMerchant
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        has_one = authority @ CouponError::UnauthorizedMerchant,
+        constraint = merchant.authority == merchant_authority.key() @ CouponError::UnauthorizedMerchant
     )]
     pub merchant: Account<'info, Merchant>,
 
+    /// Platform config - decides whether unverified merchants are blocked or flagged
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
     /// Coupon data account (PDA derived from NFT mint address)
     /// Seeds: ["coupon", nft_mint_pubkey]
-    /// Stores discount %, expiry date, category, redemption tracking, price
+    /// Stores discount kind, expiry date, category, redemption tracking, price, terms
+    /// Sized to the actual title and description length
     #[account(
         init,
         payer = merchant_authority,
-        space = CouponData::LEN,
+        space = CouponData::space(&title, &description),
         seeds = [b"coupon", nft_mint.key().as_ref()],
         bump
     )]
@@ -86,17 +96,37 @@ pub fn handler(
     ctx: Context<CreateCoupon>,
     title: String,
     description: String,
-    discount_percentage: u8,
+    discount_kind: DiscountKind,
     expiry_date: i64,
     category: CouponCategory,
     max_redemptions: u8,
     metadata_uri: String,
     price: u64, // NEW: Price in lamports (0 = free, >0 = paid)
+    terms_hash: [u8; 32],
 ) -> Result<()> {
     // Validate inputs
     require!(
-        discount_percentage > 0 && discount_percentage <= 100,
-        CouponError::InvalidDiscountPercentage
+        title.len() <= CouponData::MAX_TITLE_LEN,
+        CouponError::TitleTooLong
+    );
+    require!(
+        description.len() <= CouponData::MAX_DESCRIPTION_LEN,
+        CouponError::DescriptionTooLong
+    );
+
+    if let DiscountKind::Percentage { pct } | DiscountKind::PercentageCapped { pct, .. } = discount_kind {
+        require!(
+            pct > 0 && pct <= 100,
+            CouponError::InvalidDiscountPercentage
+        );
+    }
+    require!(discount_kind.is_valid(), CouponError::InvalidDiscount);
+
+    // Unverified merchants are either blocked or flagged, per platform config
+    let merchant_verified = ctx.accounts.merchant.is_verified();
+    require!(
+        merchant_verified || !ctx.accounts.platform_config.require_verified_merchants,
+        CouponError::MerchantNotVerified
     );
 
     let current_time = Clock::get()?.unix_timestamp;
@@ -114,7 +144,9 @@ pub fn handler(
     let coupon_data = &mut ctx.accounts.coupon_data;
     coupon_data.mint = ctx.accounts.nft_mint.key();
     coupon_data.merchant = ctx.accounts.merchant.key();
-    coupon_data.discount_percentage = discount_percentage;
+    coupon_data.discount_percentage = discount_kind.percentage();
+    coupon_data.discount_kind = discount_kind;
+    coupon_data.balance = discount_kind.initial_balance();
     coupon_data.expiry_date = expiry_date;
     coupon_data.category = category;
     coupon_data.redemptions_remaining = max_redemptions;
@@ -122,6 +154,35 @@ pub fn handler(
     coupon_data.is_active = true;
     coupon_data.price = price; // NEW: Store price
     coupon_data.bump = ctx.bumps.coupon_data;
+    coupon_data.allowlist_root = [0u8; 32]; // Open until merchant sets an allowlist
+    coupon_data.gate_kind = GateKind::None; // Ungated until merchant sets a token gate
+    coupon_data.gate_mint = Pubkey::default();
+    coupon_data.gate_min_amount = 0;
+    coupon_data.sale_start = 0; // Windows default to [creation, expiry_date)
+    coupon_data.sale_end = 0;
+    coupon_data.redeem_start = 0;
+    coupon_data.redeem_end = 0;
+    coupon_data.redemption_schedule = None; // Redeemable at any hour
+    coupon_data.min_seconds_between_redemptions = 0; // No cooldown
+    coupon_data.last_redeemed_at = 0;
+    coupon_data.title = title.clone();
+    coupon_data.description = description;
+    coupon_data.terms_hash = terms_hash;
+    coupon_data.version = CouponData::CURRENT_VERSION;
+    coupon_data.escrowed_lamports = 0;
+    coupon_data.settle_after = 0;
+    coupon_data.purchaser = Pubkey::default(); // Set by purchase_coupon
+    coupon_data.price_curve = None; // Fixed price until merchant sets a curve
+    coupon_data.refund_owed = false;
+    coupon_data.paid_lamports = 0; // Set by purchase_coupon / settle_auction
+    coupon_data.reserved = [0u8; 7];
+
+    // Flag coupons from unverified merchants in the NFT metadata symbol
+    let nft_symbol = if merchant_verified {
+        String::new()
+    } else {
+        PlatformConfig::UNVERIFIED_SYMBOL.to_string()
+    };
 
     // Truncate title to Metaplex's 32-character limit
     let nft_name = if title.len() > 32 {
@@ -145,6 +206,7 @@ pub fn handler(
         .sysvar_instructions(&ctx.accounts.sysvar_instructions.to_account_info())
         .spl_token_program(Some(&ctx.accounts.token_program.to_account_info()))
         .name(nft_name)
+        .symbol(nft_symbol)
         .uri(metadata_uri)
         .seller_fee_basis_points(0)
         .token_standard(TokenStandard::NonFungible)
@@ -238,12 +300,42 @@ pub fn handler(
         .total_coupons_created
         .checked_add(1)
         .ok_or(CouponError::ArithmeticOverflow)?;
+    merchant.open_coupons = merchant
+        .open_coupons
+        .checked_add(1)
+        .ok_or(CouponError::ArithmeticOverflow)?;
 
     msg!(
-        "NFT Coupon created: {} - {}% discount",
+        "NFT Coupon created: {} - {:?}",
         coupon_data.mint,
-        discount_percentage
+        discount_kind
     );
 
+    // Emit creation event so POS integrations and indexers learn the discount terms
+    emit!(CouponCreatedEvent {
+        nft_mint: coupon_data.mint,
+        merchant: coupon_data.merchant,
+        discount_kind,
+        price,
+        expiry_date,
+        category,
+        max_redemptions,
+        timestamp: current_time,
+    });
+
     Ok(())
 }
+
+#[event]
+pub struct CouponCreatedEvent {
+    pub nft_mint: Pubkey,
+    pub merchant: Pubkey,
+    /// Tells POS integrations which discount to apply
+    pub discount_kind: DiscountKind,
+    /// Price in lamports (0 = free claim)
+    pub price: u64,
+    pub expiry_date: i64,
+    pub category: CouponCategory,
+    pub max_redemptions: u8,
+    pub timestamp: i64,
+}
@@ -0,0 +1,145 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use crate::state::*;
+use crate::errors::*;
+
+/// Auction an unsold coupon NFT straight out of the merchant's Escrow PDA
+///
+/// For scarce premium deals where a fixed price would undersell.
+/// Settlement counts as a primary sale (stats, deferred settlement, refunds).
+/// Bidding must fit inside the coupon's sale window, and coupons restricted to
+/// an allowlist or token gate can't be auctioned (bids aren't checked against them).
+#[derive(Accounts)]
+pub struct CreatePrimaryAuction<'info> {
+    #[account(
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        has_one = authority @ CouponError::UnauthorizedMerchant,
+        constraint = !merchant.is_suspended @ CouponError::MerchantSuspended,
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    /// Coupon data account (PDA derived from NFT mint address)
+    #[account(
+        seeds = [b"coupon", nft_mint.key().as_ref()],
+        bump = coupon_data.bump,
+        has_one = merchant @ CouponError::UnauthorizedMerchant,
+        constraint = coupon_data.is_active @ CouponError::CouponInactive,
+    )]
+    pub coupon_data: Account<'info, CouponData>,
+
+    /// NFT mint account
+    pub nft_mint: Account<'info, Mint>,
+
+    /// NFT Escrow PDA - holds NFTs minted by create_coupon
+    /// Seeds: ["nft_escrow", merchant_pda, nft_mint]
+    #[account(
+        mut,
+        seeds = [b"nft_escrow", merchant.key().as_ref(), nft_mint.key().as_ref()],
+        bump,
+        token::mint = nft_mint,
+        token::authority = merchant,
+        constraint = nft_escrow.amount == 1 @ CouponError::InvalidNFTAmount,
+    )]
+    pub nft_escrow: Account<'info, TokenAccount>,
+
+    /// Auction PDA
+    /// Seeds: ["auction", nft_mint]
+    #[account(
+        init,
+        payer = authority,
+        space = Auction::LEN,
+        seeds = [b"auction", nft_mint.key().as_ref()],
+        bump,
+    )]
+    pub auction: Account<'info, Auction>,
+
+    /// Auction Escrow PDA - holds the NFT while bidding is open
+    /// Seeds: ["auction_escrow", nft_mint]
+    /// Authority: Auction PDA
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"auction_escrow", nft_mint.key().as_ref()],
+        bump,
+        token::mint = nft_mint,
+        token::authority = auction,
+    )]
+    pub auction_escrow: Account<'info, TokenAccount>,
+
+    /// Merchant authority - pays for the auction accounts, receives proceeds
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<CreatePrimaryAuction>,
+    reserve_price: u64,
+    end_time: i64,
+    min_increment: u64,
+) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let coupon_data = &ctx.accounts.coupon_data;
+    require!(
+        end_time > current_time
+            && end_time < coupon_data.expiry_date
+            && min_increment > 0,
+        CouponError::InvalidAuction
+    );
+
+    // Same sale window as claim/purchase: open now and not closing before bidding does
+    coupon_data.require_on_sale(current_time)?;
+    require!(
+        coupon_data.sale_end == 0 || end_time <= coupon_data.sale_end,
+        CouponError::InvalidAuction
+    );
+    require!(
+        !coupon_data.has_allowlist() && coupon_data.gate_kind == GateKind::None,
+        CouponError::RestrictedCouponAuction
+    );
+
+    // Get PDA signer seeds for merchant (the escrow's authority)
+    let authority_key = ctx.accounts.merchant.seed_authority.key();
+    let merchant_bump = ctx.accounts.merchant.bump;
+    let merchant_seeds = &[
+        b"merchant".as_ref(),
+        authority_key.as_ref(),
+        &[merchant_bump],
+    ];
+    let signer = &[&merchant_seeds[..]];
+
+    // Move the NFT from the merchant's Escrow PDA into the Auction Escrow PDA
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.nft_escrow.to_account_info(),
+        to: ctx.accounts.auction_escrow.to_account_info(),
+        authority: ctx.accounts.merchant.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token::transfer(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer), 1)?;
+
+    let auction = &mut ctx.accounts.auction;
+    auction.mint = ctx.accounts.nft_mint.key();
+    auction.merchant = ctx.accounts.merchant.key();
+    auction.seller = ctx.accounts.authority.key();
+    auction.is_primary = true;
+    auction.reserve_price = reserve_price;
+    auction.min_increment = min_increment;
+    auction.end_time = end_time;
+    auction.coupon_expiry = ctx.accounts.coupon_data.expiry_date;
+    auction.highest_bidder = Pubkey::default();
+    auction.highest_bid = 0;
+    auction.bump = ctx.bumps.auction;
+
+    msg!(
+        "✅ Primary auction created for {} by merchant {}: reserve {} lamports, ends {}",
+        auction.mint,
+        ctx.accounts.merchant.business_name,
+        reserve_price,
+        end_time
+    );
+
+    Ok(())
+}
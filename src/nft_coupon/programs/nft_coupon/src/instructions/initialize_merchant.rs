@@ -34,6 +34,20 @@ pub fn handler(
     merchant.business_name = business_name;
     merchant.total_coupons_created = 0;
     merchant.bump = ctx.bumps.merchant;
+    merchant.version = Merchant::CURRENT_VERSION;
+    merchant.seed_authority = ctx.accounts.authority.key();
+    merchant.logo_uri = String::new();
+    merchant.website = String::new();
+    merchant.category = CouponCategory::default();
+    merchant.pending_authority = None;
+    merchant.verification_status = VerificationStatus::Unverified;
+    merchant.verifier = Pubkey::default();
+    merchant.is_suspended = false;
+    merchant.open_coupons = 0;
+    merchant.deferred_settlement = false; // Paid directly until configure_settlement
+    merchant.holdback_seconds = 0;
+    merchant.unique_redeemers = 0;
+    merchant.reserved = [0u8; 13];
 
     msg!("Merchant initialized: {}", merchant.business_name);
 
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use crate::program::NftCoupon;
+use crate::state::*;
+use crate::errors::*;
+
+/// Initialize the platform config singleton
+///
+/// Only the program's upgrade authority can initialize it, so the admin
+/// role can't be front-run by whoever calls first after deployment.
+#[derive(Accounts)]
+pub struct InitializePlatform<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = PlatformConfig::LEN,
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ CouponError::UnauthorizedAdmin
+    )]
+    pub program: Program<'info, NftCoupon>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ CouponError::UnauthorizedAdmin
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    /// Program upgrade authority - becomes the platform admin
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<InitializePlatform>,
+    require_verified_merchants: bool,
+    fee_wallet: Pubkey,
+) -> Result<()> {
+    let platform_config = &mut ctx.accounts.platform_config;
+    platform_config.admin = ctx.accounts.admin.key();
+    platform_config.require_verified_merchants = require_verified_merchants;
+    platform_config.bump = ctx.bumps.platform_config;
+    platform_config.fee_wallet = fee_wallet;
+    platform_config.reserved = [0u8; 32];
+
+    msg!(
+        "Platform initialized: admin {} | require verified merchants: {} | fee wallet {}",
+        platform_config.admin,
+        require_verified_merchants,
+        fee_wallet
+    );
+
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::*;
+use crate::errors::*;
+use crate::bundle;
+
+/// List several coupon NFTs as one bundle for one price
+///
+/// Per coupon, `remaining_accounts` holds (in order):
+/// 1. CouponData PDA
+/// 2. Merchant PDA of that coupon
+/// 3. Seller's token account holding the NFT (writable)
+/// 4. Bundle PDA's associated token account for the mint (writable, created beforehand)
+///
+/// Every NFT moves into the bundle's escrow until purchase_bundle or cancel_bundle.
+#[derive(Accounts)]
+#[instruction(bundle_id: u64)]
+pub struct ListBundle<'info> {
+    /// Bundle listing PDA
+    /// Seeds: ["bundle", seller, bundle_id]
+    #[account(
+        init,
+        payer = seller,
+        space = BundleListing::LEN,
+        seeds = [b"bundle", seller.key().as_ref(), &bundle_id.to_le_bytes()],
+        bump,
+    )]
+    pub bundle_listing: Account<'info, BundleListing>,
+
+    /// Seller - owns the NFTs and lists them together
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ListBundle<'info>>,
+    bundle_id: u64,
+    price_lamports: u64,
+) -> Result<()> {
+    require!(price_lamports > 0, CouponError::InvalidPrice);
+
+    let groups = ctx
+        .remaining_accounts
+        .chunks_exact(BundleListing::ACCOUNTS_PER_COUPON);
+    require!(
+        groups.remainder().is_empty()
+            && (2..=BundleListing::MAX_COUPONS).contains(&groups.len()),
+        CouponError::InvalidBundleSize
+    );
+
+    let current_time = Clock::get()?.unix_timestamp;
+    let bundle_key = ctx.accounts.bundle_listing.key();
+    let mut mints = Vec::with_capacity(groups.len());
+
+    for group in groups {
+        let coupon_data = bundle::load_tradeable_coupon(&group[0], &group[1], current_time)?;
+        require!(!mints.contains(&coupon_data.mint), CouponError::BundleMismatch);
+
+        // Seller must hold the NFT
+        let seller_token_account = Account::<TokenAccount>::try_from(&group[2])?;
+        require_keys_eq!(seller_token_account.mint, coupon_data.mint, CouponError::BundleMismatch);
+        require_keys_eq!(
+            seller_token_account.owner,
+            ctx.accounts.seller.key(),
+            CouponError::InvalidNFTAmount
+        );
+        require!(seller_token_account.amount == 1, CouponError::InvalidNFTAmount);
+
+        bundle::require_bundle_escrow(&group[3], &bundle_key, &coupon_data.mint)?;
+
+        // Move the NFT into the bundle escrow
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: group[2].clone(),
+                    to: group[3].clone(),
+                    authority: ctx.accounts.seller.to_account_info(),
+                },
+            ),
+            1,
+        )?;
+
+        mints.push(coupon_data.mint);
+    }
+
+    let bundle_listing = &mut ctx.accounts.bundle_listing;
+    bundle_listing.seller = ctx.accounts.seller.key();
+    bundle_listing.bundle_id = bundle_id;
+    bundle_listing.price = price_lamports;
+    bundle_listing.mints = mints;
+    bundle_listing.bump = ctx.bumps.bundle_listing;
+
+    msg!(
+        "✅ Bundle #{} listed by {}: {} coupons for {} lamports",
+        bundle_id,
+        bundle_listing.seller,
+        bundle_listing.mints.len(),
+        price_lamports
+    );
+
+    Ok(())
+}
@@ -3,6 +3,8 @@ use anchor_spl::{
     associated_token::AssociatedToken,
     token::{self, Mint, Token, TokenAccount, Transfer},
 };
+use crate::state::*;
+use crate::errors::*;
 
 /// List an NFT coupon for resale
 ///
@@ -12,13 +14,31 @@ use anchor_spl::{
 /// This is the industry-standard approach used by Magic Eden, OpenSea, Tensor, etc.
 ///
 /// Flow:
-/// 1. Seller lists NFT (this instruction) - NFT transferred to Resale Escrow PDA
+/// 1. Seller lists NFT (this instruction) - NFT transferred to Resale Escrow PDA,
+///    asking price and expiry (default: coupon expiry) recorded in the ResaleListing PDA
 /// 2. Buyer purchases (purchase_from_resale instruction) - NFT transferred from escrow to buyer
 #[derive(Accounts)]
 pub struct ListForResale<'info> {
     /// NFT mint account
     pub nft_mint: Account<'info, Mint>,
 
+    /// Coupon data account (PDA derived from NFT mint address)
+    /// Links the traded NFT to its merchant and gates trading on its status
+    #[account(
+        seeds = [b"coupon", nft_mint.key().as_ref()],
+        bump = coupon_data.bump,
+        has_one = merchant @ CouponError::UnauthorizedMerchant,
+    )]
+    pub coupon_data: Account<'info, CouponData>,
+
+    /// Merchant account - resales are blocked while the merchant is suspended
+    #[account(
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        constraint = !merchant.is_suspended @ CouponError::MerchantSuspended,
+    )]
+    pub merchant: Account<'info, Merchant>,
+
     /// Seller's token account (source) - must have amount = 1 (owns NFT)
     #[account(
         mut,
@@ -41,6 +61,17 @@ pub struct ListForResale<'info> {
     )]
     pub resale_escrow: Account<'info, TokenAccount>,
 
+    /// Resale listing PDA - records the asking price
+    /// Seeds: ["listing", nft_mint, seller]
+    #[account(
+        init,
+        payer = seller,
+        space = ResaleListing::LEN,
+        seeds = [b"listing", nft_mint.key().as_ref(), seller.key().as_ref()],
+        bump,
+    )]
+    pub listing: Account<'info, ResaleListing>,
+
     /// Seller - owns the NFT and lists it for resale
     #[account(mut)]
     pub seller: Signer<'info>,
@@ -50,7 +81,26 @@ pub struct ListForResale<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<ListForResale>) -> Result<()> {
+pub fn handler(
+    ctx: Context<ListForResale>,
+    price_lamports: u64,
+    expires_at: Option<i64>,
+) -> Result<()> {
+    // Validate price
+    require!(price_lamports > 0, CouponError::InvalidPrice);
+
+    // Validate coupon is still usable (active, unexpired, uses left)
+    let current_time = Clock::get()?.unix_timestamp;
+    ctx.accounts.coupon_data.require_tradeable(current_time)?;
+
+    // Listing expires with the coupon unless the seller picks an earlier time
+    let coupon_expiry = ctx.accounts.coupon_data.expiry_date;
+    let expires_at = expires_at.unwrap_or(coupon_expiry);
+    require!(
+        expires_at > current_time && expires_at <= coupon_expiry,
+        CouponError::InvalidListingExpiry
+    );
+
     // Transfer NFT from seller's wallet to Resale Escrow PDA
     let cpi_accounts = Transfer {
         from: ctx.accounts.seller_token_account.to_account_info(),
@@ -69,6 +119,15 @@ pub fn handler(ctx: Context<ListForResale>) -> Result<()> {
     );
     msg!("   NFT transferred to Resale Escrow PDA");
 
+    let listing = &mut ctx.accounts.listing;
+    listing.mint = ctx.accounts.nft_mint.key();
+    listing.seller = ctx.accounts.seller.key();
+    listing.price = price_lamports;
+    listing.expires_at = expires_at;
+    listing.bump = ctx.bumps.listing;
+
+    msg!("   Asking price: {} lamports (expires {})", price_lamports, expires_at);
+
     Ok(())
 }
 
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+use crate::migration;
+
+/// Migrate a CouponData account to the current layout version
+///
+/// Permissionless: anyone can pay the rent difference to upgrade a live coupon.
+#[derive(Accounts)]
+pub struct MigrateCoupon<'info> {
+    /// CHECK: Old-layout CouponData can't be deserialized as Account<CouponData>
+    /// Owner and discriminator are validated in the handler
+    #[account(mut)]
+    pub coupon_data: UncheckedAccount<'info>,
+
+    /// Pays for any extra rent needed by the larger layout
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<MigrateCoupon>) -> Result<()> {
+    let info = ctx.accounts.coupon_data.to_account_info();
+    let mut coupon_data: CouponData =
+        migration::load_zero_extended(&info, CouponData::MAX_LEN)?;
+    let from_version = coupon_data.version;
+
+    require!(
+        from_version < CouponData::CURRENT_VERSION,
+        CouponError::AlreadyMigrated
+    );
+
+    // v0 -> v1: legacy coupons only knew percentage discounts
+    if from_version < 1 {
+        coupon_data.discount_kind = DiscountKind::Percentage {
+            pct: coupon_data.discount_percentage,
+        };
+    }
+    // v1 -> v2: price_curve decodes as None (fixed price), nothing to fix up
+    coupon_data.version = CouponData::CURRENT_VERSION;
+
+    let new_len = CouponData::space(&coupon_data.title, &coupon_data.description);
+    migration::resize_and_store(
+        &info,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        new_len,
+        &coupon_data,
+    )?;
+
+    msg!(
+        "Coupon {} migrated: v{} -> v{}",
+        coupon_data.mint,
+        from_version,
+        coupon_data.version
+    );
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+use crate::migration;
+
+/// Migrate a Merchant account to the current layout version
+///
+/// Permissionless: anyone can pay the rent difference to upgrade a merchant.
+#[derive(Accounts)]
+pub struct MigrateMerchant<'info> {
+    /// CHECK: Old-layout Merchant can't be deserialized as Account<Merchant>
+    /// Owner and discriminator are validated in the handler
+    #[account(mut)]
+    pub merchant: UncheckedAccount<'info>,
+
+    /// Pays for any extra rent needed by the larger layout
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<MigrateMerchant>) -> Result<()> {
+    let info = ctx.accounts.merchant.to_account_info();
+    let mut merchant: Merchant = migration::load_zero_extended(&info, Merchant::LEN)?;
+    let from_version = merchant.version;
+
+    require!(
+        from_version < Merchant::CURRENT_VERSION,
+        CouponError::AlreadyMigrated
+    );
+
+    // v1 -> v2: PDA seed key recorded separately so the authority can rotate
+    if from_version < 2 {
+        merchant.seed_authority = merchant.authority;
+    }
+    // v2 -> v3: coupons could not be closed before v3, so every created coupon is open
+    if from_version < 3 {
+        merchant.open_coupons = merchant.total_coupons_created;
+    }
+    // v3 -> v4: stats counters start at zero (history before v4 lives in the indexer)
+    merchant.version = Merchant::CURRENT_VERSION;
+
+    migration::resize_and_store(
+        &info,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        Merchant::LEN,
+        &merchant,
+    )?;
+
+    msg!(
+        "Merchant {} migrated: v{} -> v{}",
+        merchant.business_name,
+        from_version,
+        merchant.version
+    );
+
+    Ok(())
+}
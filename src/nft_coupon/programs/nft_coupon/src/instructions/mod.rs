@@ -3,17 +3,95 @@ pub mod create_coupon;
 pub mod claim_coupon;
 pub mod purchase_coupon;
 pub mod redeem_coupon;
+pub mod redeem_amount;
 pub mod update_coupon_status;
+pub mod batch_update_coupon_status;
 pub mod transfer_coupon;
 pub mod list_for_resale;
 pub mod purchase_from_resale;
+pub mod update_listing_price;
+pub mod crank_expired_listing;
+pub mod set_coupon_allowlist;
+pub mod set_coupon_gate;
+pub mod set_coupon_windows;
+pub mod set_redemption_schedule;
+pub mod set_redemption_cooldown;
+pub mod migrate_coupon;
+pub mod migrate_merchant;
+pub mod update_merchant_profile;
+pub mod propose_authority;
+pub mod accept_authority;
+pub mod initialize_platform;
+pub mod update_platform_config;
+pub mod verify_merchant;
+pub mod revoke_merchant;
+pub mod set_merchant_suspension;
+pub mod close_coupon;
+pub mod close_merchant;
+pub mod configure_settlement;
+pub mod withdraw_revenue;
+pub mod refund_coupon;
+pub mod place_offer;
+pub mod cancel_offer;
+pub mod accept_offer;
+pub mod place_collection_offer;
+pub mod cancel_collection_offer;
+pub mod accept_collection_offer;
+pub mod create_auction;
+pub mod create_primary_auction;
+pub mod place_bid;
+pub mod settle_auction;
+pub mod set_price_curve;
+pub mod list_bundle;
+pub mod purchase_bundle;
+pub mod cancel_bundle;
+pub mod cancel_listing;
 
 pub use initialize_merchant::*;
 pub use create_coupon::*;
 pub use claim_coupon::*;
 pub use purchase_coupon::*;
 pub use redeem_coupon::*;
+pub use redeem_amount::*;
 pub use update_coupon_status::*;
+pub use batch_update_coupon_status::*;
 pub use transfer_coupon::*;
 pub use list_for_resale::*;
 pub use purchase_from_resale::*;
+pub use update_listing_price::*;
+pub use crank_expired_listing::*;
+pub use set_coupon_allowlist::*;
+pub use set_coupon_gate::*;
+pub use set_coupon_windows::*;
+pub use set_redemption_schedule::*;
+pub use set_redemption_cooldown::*;
+pub use migrate_coupon::*;
+pub use migrate_merchant::*;
+pub use update_merchant_profile::*;
+pub use propose_authority::*;
+pub use accept_authority::*;
+pub use initialize_platform::*;
+pub use update_platform_config::*;
+pub use verify_merchant::*;
+pub use revoke_merchant::*;
+pub use set_merchant_suspension::*;
+pub use close_coupon::*;
+pub use close_merchant::*;
+pub use configure_settlement::*;
+pub use withdraw_revenue::*;
+pub use refund_coupon::*;
+pub use place_offer::*;
+pub use cancel_offer::*;
+pub use accept_offer::*;
+pub use place_collection_offer::*;
+pub use cancel_collection_offer::*;
+pub use accept_collection_offer::*;
+pub use create_auction::*;
+pub use create_primary_auction::*;
+pub use place_bid::*;
+pub use settle_auction::*;
+pub use set_price_curve::*;
+pub use list_bundle::*;
+pub use purchase_bundle::*;
+pub use cancel_bundle::*;
+pub use cancel_listing::*;
@@ -0,0 +1,123 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::system_instruction;
+use crate::state::*;
+use crate::errors::*;
+
+/// Bid on an auction
+///
+/// The bid is locked in the Auction PDA and the previous top bidder is
+/// refunded in the same transaction. Bids in the final
+/// `Auction::ANTI_SNIPE_SECONDS` extend the auction (never past the coupon's expiry).
+#[derive(Accounts)]
+pub struct PlaceBid<'info> {
+    /// Auction PDA - holds the top bid
+    /// Seeds: ["auction", nft_mint]
+    #[account(
+        mut,
+        seeds = [b"auction", auction.mint.as_ref()],
+        bump = auction.bump,
+        has_one = merchant @ CouponError::UnauthorizedMerchant,
+    )]
+    pub auction: Account<'info, Auction>,
+
+    /// Merchant account - bidding is blocked while the merchant is suspended
+    #[account(
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        constraint = !merchant.is_suspended @ CouponError::MerchantSuspended,
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    /// Previous top bidder - refunded (required once the auction has a bid)
+    /// CHECK: Must match auction.highest_bidder, verified in handler
+    #[account(mut)]
+    pub previous_bidder: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<PlaceBid>, amount: u64) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let auction = &ctx.accounts.auction;
+
+    // 1. Auction must still be open
+    require!(current_time < auction.end_time, CouponError::AuctionEnded);
+
+    // 2. Bid must meet the reserve / beat the top bid by the minimum increment
+    require!(amount >= auction.min_next_bid()?, CouponError::BidTooLow);
+
+    // 3. Lock the new bid in the Auction PDA
+    invoke(
+        &system_instruction::transfer(
+            &ctx.accounts.bidder.key(),
+            &ctx.accounts.auction.key(),
+            amount,
+        ),
+        &[
+            ctx.accounts.bidder.to_account_info(),
+            ctx.accounts.auction.to_account_info(),
+        ],
+    )?;
+
+    // 4. Refund the previous top bidder (Auction PDA is program-owned)
+    let previous_bid = ctx.accounts.auction.highest_bid;
+    if previous_bid > 0 {
+        let previous_bidder = ctx
+            .accounts
+            .previous_bidder
+            .as_ref()
+            .ok_or(CouponError::InvalidPreviousBidder)?;
+        require_keys_eq!(
+            previous_bidder.key(),
+            ctx.accounts.auction.highest_bidder,
+            CouponError::InvalidPreviousBidder
+        );
+        ctx.accounts.auction.sub_lamports(previous_bid)?;
+        previous_bidder.add_lamports(previous_bid)?;
+
+        msg!("Previous bidder {} refunded {} lamports", previous_bidder.key(), previous_bid);
+    }
+
+    // 5. Record the new top bid, extending the auction on late bids (anti-sniping)
+    let auction = &mut ctx.accounts.auction;
+    auction.highest_bidder = ctx.accounts.bidder.key();
+    auction.highest_bid = amount;
+    // The extension stops short of the coupon's expiry so the winner never gets an expired coupon
+    if auction.end_time - current_time < Auction::ANTI_SNIPE_SECONDS {
+        let extended = (current_time + Auction::ANTI_SNIPE_SECONDS).min(auction.coupon_expiry - 1);
+        if extended > auction.end_time {
+            auction.end_time = extended;
+            msg!("Late bid - auction extended to {}", auction.end_time);
+        }
+    }
+
+    msg!(
+        "✅ Bid on {} by {}: {} lamports",
+        auction.mint,
+        auction.highest_bidder,
+        amount
+    );
+
+    emit!(BidPlacedEvent {
+        nft_mint: auction.mint,
+        bidder: auction.highest_bidder,
+        amount,
+        end_time: auction.end_time,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct BidPlacedEvent {
+    pub nft_mint: Pubkey,
+    pub bidder: Pubkey,
+    pub amount: u64,
+    pub end_time: i64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::system_instruction;
+use crate::state::*;
+use crate::errors::*;
+
+/// Place a floor bid on any coupon from a merchant
+///
+/// Optional filters (category, minimum discount) are checked against the
+/// coupon's CouponData when a holder accepts. The offered SOL is locked in
+/// the CollectionOffer PDA until filled or cancelled.
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct PlaceCollectionOffer<'info> {
+    /// Merchant account - trading is blocked while the merchant is suspended
+    #[account(
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        constraint = !merchant.is_suspended @ CouponError::MerchantSuspended,
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    /// Collection offer PDA - holds the offered SOL
+    /// Seeds: ["collection_offer", merchant, bidder, offer_id]
+    #[account(
+        init,
+        payer = bidder,
+        space = CollectionOffer::LEN,
+        seeds = [
+            b"collection_offer",
+            merchant.key().as_ref(),
+            bidder.key().as_ref(),
+            &offer_id.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub collection_offer: Account<'info, CollectionOffer>,
+
+    /// Bidder - pays the offer amount + offer account rent
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<PlaceCollectionOffer>,
+    offer_id: u64,
+    amount: u64,
+    expires_at: i64,
+    category: Option<CouponCategory>,
+    min_discount_percentage: u8,
+) -> Result<()> {
+    require!(amount > 0, CouponError::InvalidPrice);
+    require!(
+        expires_at > Clock::get()?.unix_timestamp,
+        CouponError::InvalidOfferExpiry
+    );
+    require!(
+        min_discount_percentage <= 100,
+        CouponError::InvalidDiscount
+    );
+
+    // Lock the offered SOL in the CollectionOffer PDA
+    invoke(
+        &system_instruction::transfer(
+            &ctx.accounts.bidder.key(),
+            &ctx.accounts.collection_offer.key(),
+            amount,
+        ),
+        &[
+            ctx.accounts.bidder.to_account_info(),
+            ctx.accounts.collection_offer.to_account_info(),
+        ],
+    )?;
+
+    let collection_offer = &mut ctx.accounts.collection_offer;
+    collection_offer.merchant = ctx.accounts.merchant.key();
+    collection_offer.bidder = ctx.accounts.bidder.key();
+    collection_offer.offer_id = offer_id;
+    collection_offer.amount = amount;
+    collection_offer.expires_at = expires_at;
+    collection_offer.category = category;
+    collection_offer.min_discount_percentage = min_discount_percentage;
+    collection_offer.bump = ctx.bumps.collection_offer;
+
+    msg!(
+        "✅ Collection offer #{} placed on merchant {} by {}: {} lamports (min discount {}%)",
+        offer_id,
+        ctx.accounts.merchant.business_name,
+        collection_offer.bidder,
+        amount,
+        min_discount_percentage
+    );
+
+    Ok(())
+}
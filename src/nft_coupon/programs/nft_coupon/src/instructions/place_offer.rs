@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::system_instruction;
+use anchor_spl::token::Mint;
+use crate::state::*;
+use crate::errors::*;
+
+/// Place an offer on a specific coupon NFT (listed or not)
+///
+/// The offered SOL is locked in the Offer PDA until the holder accepts it
+/// (accept_offer) or the bidder withdraws it (cancel_offer).
+#[derive(Accounts)]
+pub struct PlaceOffer<'info> {
+    /// NFT mint account
+    pub nft_mint: Account<'info, Mint>,
+
+    /// Coupon data account (PDA derived from NFT mint address)
+    /// Links the bid-on NFT to its merchant
+    #[account(
+        seeds = [b"coupon", nft_mint.key().as_ref()],
+        bump = coupon_data.bump,
+        has_one = merchant @ CouponError::UnauthorizedMerchant,
+    )]
+    pub coupon_data: Account<'info, CouponData>,
+
+    /// Merchant account - trading is blocked while the merchant is suspended
+    #[account(
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        constraint = !merchant.is_suspended @ CouponError::MerchantSuspended,
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    /// Offer PDA - holds the offered SOL
+    /// Seeds: ["offer", nft_mint, bidder]
+    #[account(
+        init,
+        payer = bidder,
+        space = Offer::LEN,
+        seeds = [b"offer", nft_mint.key().as_ref(), bidder.key().as_ref()],
+        bump,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    /// Bidder - pays the offer amount + offer account rent
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<PlaceOffer>, amount: u64, expires_at: i64) -> Result<()> {
+    require!(amount > 0, CouponError::InvalidPrice);
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(expires_at > current_time, CouponError::InvalidOfferExpiry);
+    ctx.accounts.coupon_data.require_tradeable(current_time)?;
+
+    // Lock the offered SOL in the Offer PDA
+    invoke(
+        &system_instruction::transfer(
+            &ctx.accounts.bidder.key(),
+            &ctx.accounts.offer.key(),
+            amount,
+        ),
+        &[
+            ctx.accounts.bidder.to_account_info(),
+            ctx.accounts.offer.to_account_info(),
+        ],
+    )?;
+
+    let offer = &mut ctx.accounts.offer;
+    offer.mint = ctx.accounts.nft_mint.key();
+    offer.bidder = ctx.accounts.bidder.key();
+    offer.amount = amount;
+    offer.expires_at = expires_at;
+    offer.bump = ctx.bumps.offer;
+
+    msg!(
+        "✅ Offer placed on {} by {}: {} lamports (expires {})",
+        offer.mint,
+        offer.bidder,
+        amount,
+        expires_at
+    );
+
+    Ok(())
+}
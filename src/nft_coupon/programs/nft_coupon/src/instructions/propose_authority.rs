@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+
+/// Step 1 of merchant authority rotation
+///
+/// Current authority nominates a new wallet (or cancels with None).
+/// Nothing changes until the new wallet signs accept_authority.
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        has_one = authority @ CouponError::UnauthorizedMerchant
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(
+    ctx: Context<ProposeAuthority>,
+    new_authority: Option<Pubkey>,
+) -> Result<()> {
+    let merchant = &mut ctx.accounts.merchant;
+    merchant.pending_authority = new_authority;
+
+    match new_authority {
+        Some(pending) => msg!(
+            "Merchant {} authority transfer proposed: {} -> {}",
+            merchant.key(),
+            merchant.authority,
+            pending
+        ),
+        None => msg!("Merchant {} authority transfer cancelled", merchant.key()),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,212 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::system_instruction;
+use anchor_spl::token::{self, CloseAccount, Token, TokenAccount, Transfer};
+use crate::state::*;
+use crate::errors::*;
+use crate::bundle;
+
+/// Purchase a whole bundle in one atomic transaction
+///
+/// 1. Buyer pays the bundle price once (97.5% to seller, 2.5% to platform)
+/// 2. Every NFT transferred from the bundle escrow to the buyer
+/// 3. Escrow accounts and the bundle listing closed, rent returned to the seller
+///
+/// Per coupon, `remaining_accounts` holds (in the listed order):
+/// 1. CouponData PDA
+/// 2. Merchant PDA of that coupon
+/// 3. Bundle PDA's associated token account for the mint (writable)
+/// 4. Buyer's token account for the mint (writable, created beforehand)
+#[derive(Accounts)]
+pub struct PurchaseBundle<'info> {
+    /// Bundle listing PDA - closed, rent returned to seller
+    /// Seeds: ["bundle", seller, bundle_id]
+    #[account(
+        mut,
+        close = seller,
+        seeds = [
+            b"bundle",
+            seller.key().as_ref(),
+            &bundle_listing.bundle_id.to_le_bytes(),
+        ],
+        bump = bundle_listing.bump,
+    )]
+    pub bundle_listing: Account<'info, BundleListing>,
+
+    /// Seller - receives 97.5% of payment
+    /// CHECK: Validated by bundle_listing PDA seeds
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+
+    /// Buyer - pays for the bundle
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// Platform config singleton (fee wallet)
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// Platform fee wallet - receives 2.5% of payment
+    /// CHECK: Must be the fee wallet stored in the platform config
+    #[account(
+        mut,
+        address = platform_config.fee_wallet @ CouponError::InvalidFeeWallet,
+    )]
+    pub platform_wallet: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PurchaseBundle<'info>>,
+    expected_price: u64,
+) -> Result<()> {
+    let bundle_listing = &ctx.accounts.bundle_listing;
+
+    // Validate price - never more than the buyer signed for
+    let price_lamports = bundle_listing.price;
+    require!(
+        price_lamports <= expected_price,
+        CouponError::PriceExceedsMaximum
+    );
+
+    let groups = ctx
+        .remaining_accounts
+        .chunks_exact(BundleListing::ACCOUNTS_PER_COUPON);
+    require!(
+        groups.remainder().is_empty() && groups.len() == bundle_listing.mints.len(),
+        CouponError::BundleMismatch
+    );
+
+    // Calculate payment splits once for the whole bundle
+    let platform_fee = (price_lamports
+        .checked_mul(25)
+        .ok_or(CouponError::ArithmeticOverflow)?)
+        .checked_div(1000)
+        .ok_or(CouponError::ArithmeticOverflow)?; // 2.5%
+    let seller_amount = price_lamports
+        .checked_sub(platform_fee)
+        .ok_or(CouponError::ArithmeticOverflow)?; // 97.5%
+
+    msg!(
+        "Bundle Purchase: {} coupons | Price {} lamports | Seller {} | Platform {}",
+        groups.len(),
+        price_lamports,
+        seller_amount,
+        platform_fee
+    );
+
+    // ATOMIC TRANSACTION STEP 1: Transfer SOL to seller (97.5%)
+    invoke(
+        &system_instruction::transfer(
+            &ctx.accounts.buyer.key(),
+            &ctx.accounts.seller.key(),
+            seller_amount,
+        ),
+        &[
+            ctx.accounts.buyer.to_account_info(),
+            ctx.accounts.seller.to_account_info(),
+        ],
+    )?;
+
+    // ATOMIC TRANSACTION STEP 2: Transfer SOL to platform (2.5%)
+    if platform_fee > 0 {
+        invoke(
+            &system_instruction::transfer(
+                &ctx.accounts.buyer.key(),
+                &ctx.accounts.platform_wallet.key(),
+                platform_fee,
+            ),
+            &[
+                ctx.accounts.buyer.to_account_info(),
+                ctx.accounts.platform_wallet.to_account_info(),
+            ],
+        )?;
+    }
+
+    // ATOMIC TRANSACTION STEP 3: Transfer every NFT from the bundle escrow to the buyer
+    let current_time = Clock::get()?.unix_timestamp;
+    let bundle_key = bundle_listing.key();
+    let seller_key = ctx.accounts.seller.key();
+    let bundle_id_bytes = bundle_listing.bundle_id.to_le_bytes();
+    let bump = bundle_listing.bump;
+    let seeds = &[
+        b"bundle".as_ref(),
+        seller_key.as_ref(),
+        bundle_id_bytes.as_ref(),
+        &[bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    for (group, mint) in groups.zip(bundle_listing.mints.iter()) {
+        let coupon_data = bundle::load_tradeable_coupon(&group[0], &group[1], current_time)?;
+        require_keys_eq!(coupon_data.mint, *mint, CouponError::BundleMismatch);
+        bundle::require_bundle_escrow(&group[2], &bundle_key, mint)?;
+
+        let buyer_token_account = Account::<TokenAccount>::try_from(&group[3])?;
+        require_keys_eq!(buyer_token_account.mint, *mint, CouponError::BundleMismatch);
+        require_keys_eq!(
+            buyer_token_account.owner,
+            ctx.accounts.buyer.key(),
+            CouponError::BundleMismatch
+        );
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: group[2].clone(),
+                    to: group[3].clone(),
+                    authority: ctx.accounts.bundle_listing.to_account_info(),
+                },
+                signer,
+            ),
+            1,
+        )?;
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: group[2].clone(),
+                destination: ctx.accounts.seller.to_account_info(),
+                authority: ctx.accounts.bundle_listing.to_account_info(),
+            },
+            signer,
+        ))?;
+    }
+
+    msg!(
+        "🎉 Bundle #{} purchased! Buyer: {} | Seller: {} | Price: {} lamports | Fee: {}",
+        ctx.accounts.bundle_listing.bundle_id,
+        ctx.accounts.buyer.key(),
+        seller_key,
+        price_lamports,
+        platform_fee
+    );
+
+    emit!(BundlePurchasedEvent {
+        seller: seller_key,
+        buyer: ctx.accounts.buyer.key(),
+        bundle_id: ctx.accounts.bundle_listing.bundle_id,
+        mints: ctx.accounts.bundle_listing.mints.clone(),
+        price: price_lamports,
+        platform_fee,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct BundlePurchasedEvent {
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub bundle_id: u64,
+    pub mints: Vec<Pubkey>,
+    pub price: u64,
+    pub platform_fee: u64,
+    pub timestamp: i64,
+}
@@ -7,6 +7,7 @@ use anchor_spl::{
 };
 use crate::state::*;
 use crate::errors::*;
+use crate::{gate, merkle};
 
 /// Purchase a paid coupon NFT with atomic payment + NFT transfer
 ///
@@ -14,6 +15,12 @@ use crate::errors::*;
 /// 1. User pays SOL (97.5% to merchant, 2.5% to platform)
 /// 2. NFT transferred from Escrow PDA to user
 /// 3. All or nothing (transaction fails if any step fails)
+///
+/// Dutch-auction coupons charge the price curve's current price; the buyer's
+/// `max_price` guards against paying more than they signed for.
+///
+/// Merchants with deferred settlement receive nothing up front: the full price
+/// goes to their RevenueVault and is split on withdraw_revenue.
 #[derive(Accounts)]
 pub struct PurchaseCoupon<'info> {
     /// Coupon data account (PDA derived from NFT mint address)
@@ -32,16 +39,21 @@ pub struct PurchaseCoupon<'info> {
     pub coupon_data: Account<'info, CouponData>,
 
     /// Merchant account (PDA derived from merchant authority)
+    /// Claims/purchases are blocked while the merchant is suspended
     #[account(
         mut,
-        seeds = [b"merchant", merchant.authority.as_ref()],
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
         bump,
+        constraint = !merchant.is_suspended @ CouponError::MerchantSuspended,
     )]
     pub merchant: Account<'info, Merchant>,
 
     /// Merchant authority wallet - receives 97.5% of payment
     /// CHECK: Validated by merchant.authority constraint
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = merchant_authority.key() == merchant.authority @ CouponError::UnauthorizedMerchant,
+    )]
     pub merchant_authority: UncheckedAccount<'info>,
 
     /// Platform fee wallet - receives 2.5% of payment
@@ -79,12 +91,48 @@ pub struct PurchaseCoupon<'info> {
     #[account(mut)]
     pub buyer: Signer<'info>,
 
+    /// Allowlist receipt - only required when the caller's allowlist leaf has a quota
+    /// Seeds: ["allowlist_receipt", allowlist_root, buyer]
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = AllowlistReceipt::LEN,
+        seeds = [b"allowlist_receipt", coupon_data.allowlist_root.as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub allowlist_receipt: Option<Account<'info, AllowlistReceipt>>,
+
+    /// Redeemer record for unique redeemer stats - paid here by the buyer so
+    /// redeeming later costs no rent
+    /// Seeds: ["redeemer", merchant, buyer]
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = RedeemerRecord::LEN,
+        seeds = [b"redeemer", merchant.key().as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub redeemer_record: Account<'info, RedeemerRecord>,
+
+    /// Merchant revenue vault - required when merchant.deferred_settlement is on
+    /// Seeds: ["revenue_vault", merchant]
+    #[account(
+        mut,
+        seeds = [b"revenue_vault", merchant.key().as_ref()],
+        bump = revenue_vault.bump,
+    )]
+    pub revenue_vault: Option<Account<'info, RevenueVault>>,
+
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<PurchaseCoupon>) -> Result<()> {
+pub fn handler(
+    ctx: Context<PurchaseCoupon>,
+    proof: Option<AllowlistProof>,
+    max_price: u64,
+) -> Result<()> {
     let coupon_data = &ctx.accounts.coupon_data;
 
     // Validate expiry date
@@ -94,15 +142,42 @@ pub fn handler(ctx: Context<PurchaseCoupon>) -> Result<()> {
         CouponError::CouponExpired
     );
 
+    // Validate sale window (scheduled drops)
+    coupon_data.require_on_sale(current_time)?;
+
     // Validate redemptions remaining
     require!(
         coupon_data.redemptions_remaining > 0,
         CouponError::NoRedemptionsRemaining
     );
 
-    // Calculate payment splits
+    // Validate allowlist membership (VIP-only drops)
+    merkle::require_allowlisted(
+        coupon_data,
+        &ctx.accounts.buyer.key(),
+        proof.as_ref(),
+        ctx.accounts.allowlist_receipt.as_deref_mut(),
+    )?;
+    if let (Some(receipt), Some(bump)) = (
+        ctx.accounts.allowlist_receipt.as_mut(),
+        ctx.bumps.allowlist_receipt,
+    ) {
+        receipt.bump = bump;
+    }
+
+    // Validate token gate (partner collection / DEAL-token holders)
+    gate::require_token_gate(
+        coupon_data,
+        &ctx.accounts.buyer.key(),
+        ctx.remaining_accounts,
+    )?;
+
+    // Current price (price curve or fixed) - never more than the buyer signed for
     // Price is stored in lamports (1 SOL = 1,000,000,000 lamports)
-    let total_price = coupon_data.price;
+    let total_price = coupon_data.current_price(current_time);
+    require!(total_price <= max_price, CouponError::PriceExceedsMaximum);
+
+    // Calculate payment splits
     let platform_fee = (total_price
         .checked_mul(25)
         .ok_or(CouponError::ArithmeticOverflow)?)
@@ -119,43 +194,76 @@ pub fn handler(ctx: Context<PurchaseCoupon>) -> Result<()> {
         platform_fee
     );
 
-    // ATOMIC TRANSACTION STEP 1: Transfer SOL to merchant (97.5%)
-    let transfer_merchant_ix = system_instruction::transfer(
-        &ctx.accounts.buyer.key(),
-        &ctx.accounts.merchant_authority.key(),
-        merchant_amount,
-    );
-    invoke(
-        &transfer_merchant_ix,
-        &[
-            ctx.accounts.buyer.to_account_info(),
-            ctx.accounts.merchant_authority.to_account_info(),
-        ],
-    )?;
+    if ctx.accounts.merchant.deferred_settlement {
+        // DEFERRED SETTLEMENT: Full price held in the RevenueVault
+        // Released on redemption or after the holdback period (withdraw_revenue)
+        let revenue_vault = ctx
+            .accounts
+            .revenue_vault
+            .as_mut()
+            .ok_or(CouponError::MissingRevenueVault)?;
+        invoke(
+            &system_instruction::transfer(
+                &ctx.accounts.buyer.key(),
+                &revenue_vault.key(),
+                total_price,
+            ),
+            &[
+                ctx.accounts.buyer.to_account_info(),
+                revenue_vault.to_account_info(),
+            ],
+        )?;
+        revenue_vault.escrowed_total = revenue_vault
+            .escrowed_total
+            .checked_add(total_price)
+            .ok_or(CouponError::ArithmeticOverflow)?;
 
-    msg!("✅ Merchant paid: {} lamports", merchant_amount);
+        let coupon_data = &mut ctx.accounts.coupon_data;
+        coupon_data.escrowed_lamports = total_price;
+        coupon_data.settle_after = current_time
+            .checked_add(ctx.accounts.merchant.holdback_seconds)
+            .ok_or(CouponError::ArithmeticOverflow)?;
 
-    // ATOMIC TRANSACTION STEP 2: Transfer SOL to platform (2.5%)
-    if platform_fee > 0 {
-        let transfer_platform_ix = system_instruction::transfer(
+        msg!("✅ {} lamports escrowed in revenue vault", total_price);
+    } else {
+        // ATOMIC TRANSACTION STEP 1: Transfer SOL to merchant (97.5%)
+        let transfer_merchant_ix = system_instruction::transfer(
             &ctx.accounts.buyer.key(),
-            &ctx.accounts.platform_wallet.key(),
-            platform_fee,
+            &ctx.accounts.merchant_authority.key(),
+            merchant_amount,
         );
         invoke(
-            &transfer_platform_ix,
+            &transfer_merchant_ix,
             &[
                 ctx.accounts.buyer.to_account_info(),
-                ctx.accounts.platform_wallet.to_account_info(),
+                ctx.accounts.merchant_authority.to_account_info(),
             ],
         )?;
 
-        msg!("✅ Platform fee paid: {} lamports", platform_fee);
+        msg!("✅ Merchant paid: {} lamports", merchant_amount);
+
+        // ATOMIC TRANSACTION STEP 2: Transfer SOL to platform (2.5%)
+        if platform_fee > 0 {
+            let transfer_platform_ix = system_instruction::transfer(
+                &ctx.accounts.buyer.key(),
+                &ctx.accounts.platform_wallet.key(),
+                platform_fee,
+            );
+            invoke(
+                &transfer_platform_ix,
+                &[
+                    ctx.accounts.buyer.to_account_info(),
+                    ctx.accounts.platform_wallet.to_account_info(),
+                ],
+            )?;
+
+            msg!("✅ Platform fee paid: {} lamports", platform_fee);
+        }
     }
 
     // ATOMIC TRANSACTION STEP 3: Transfer NFT from Escrow PDA to Buyer
     // Get PDA signer seeds for merchant (the escrow's authority)
-    let authority_key = ctx.accounts.merchant.authority.key();
+    let authority_key = ctx.accounts.merchant.seed_authority.key();
     let merchant_bump = ctx.bumps.merchant;
     let merchant_seeds = &[
         b"merchant".as_ref(),
@@ -184,11 +292,21 @@ pub fn handler(ctx: Context<PurchaseCoupon>) -> Result<()> {
 
     // Update coupon data - decrement redemptions remaining
     let coupon_data = &mut ctx.accounts.coupon_data;
+    coupon_data.purchaser = ctx.accounts.buyer.key();
+    coupon_data.paid_lamports = total_price; // Refund amount if the merchant cancels
     coupon_data.redemptions_remaining = coupon_data
         .redemptions_remaining
         .checked_sub(1)
         .ok_or(CouponError::ArithmeticOverflow)?;
 
+    ctx.accounts.merchant.record_sale(total_price, platform_fee)?;
+    let merchant_key = ctx.accounts.merchant.key();
+    ctx.accounts.redeemer_record.init(
+        ctx.accounts.buyer.key(),
+        merchant_key,
+        ctx.bumps.redeemer_record,
+    );
+
     msg!(
         "🎉 Purchase complete! Buyer: {} | Price: {} lamports | Merchant: {} | Platform: {}",
         ctx.accounts.buyer.key(),
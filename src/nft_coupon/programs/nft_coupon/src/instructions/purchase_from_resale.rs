@@ -5,7 +5,9 @@ use anchor_spl::{
     associated_token::AssociatedToken,
     token::{self, Mint, Token, TokenAccount, Transfer},
 };
+use crate::state::*;
 use crate::errors::*;
+use super::transfer_coupon::CouponTradedEvent;
 
 /// Purchase an NFT coupon from resale marketplace
 ///
@@ -14,12 +16,32 @@ use crate::errors::*;
 /// 2. NFT transferred from Resale Escrow PDA to buyer
 /// 3. All or nothing (transaction fails if any step fails)
 ///
+/// The buyer pays the listing's asking price, and never more than `expected_price`
+/// (protects against the seller raising the price while the purchase is pending).
+///
 /// This is the industry-standard approach used by Magic Eden, OpenSea, Tensor, etc.
 #[derive(Accounts)]
 pub struct PurchaseFromResale<'info> {
     /// NFT mint account
     pub nft_mint: Account<'info, Mint>,
 
+    /// Coupon data account (PDA derived from NFT mint address)
+    /// Links the traded NFT to its merchant and gates trading on its status
+    #[account(
+        seeds = [b"coupon", nft_mint.key().as_ref()],
+        bump = coupon_data.bump,
+        has_one = merchant @ CouponError::UnauthorizedMerchant,
+    )]
+    pub coupon_data: Account<'info, CouponData>,
+
+    /// Merchant account - resales are blocked while the merchant is suspended
+    #[account(
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        constraint = !merchant.is_suspended @ CouponError::MerchantSuspended,
+    )]
+    pub merchant: Account<'info, Merchant>,
+
     /// Resale Escrow PDA - holds NFT until purchased
     /// Seeds: ["resale_escrow", nft_mint, seller]
     /// Authority: Resale Escrow PDA itself (self-custodial, can sign via PDA)
@@ -33,6 +55,16 @@ pub struct PurchaseFromResale<'info> {
     )]
     pub resale_escrow: Account<'info, TokenAccount>,
 
+    /// Resale listing PDA - closed, rent returned to seller
+    /// Seeds: ["listing", nft_mint, seller]
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"listing", nft_mint.key().as_ref(), seller.key().as_ref()],
+        bump = listing.bump,
+    )]
+    pub listing: Account<'info, ResaleListing>,
+
     /// Buyer's token account (destination) - created if not exists
     #[account(
         init_if_needed,
@@ -61,9 +93,21 @@ pub struct PurchaseFromResale<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<PurchaseFromResale>, price_lamports: u64) -> Result<()> {
-    // Validate price
-    require!(price_lamports > 0, CouponError::InvalidPrice);
+pub fn handler(ctx: Context<PurchaseFromResale>, expected_price: u64) -> Result<()> {
+    // Validate price - never more than the buyer signed for
+    let price_lamports = ctx.accounts.listing.price;
+    require!(
+        price_lamports <= expected_price,
+        CouponError::PriceExceedsMaximum
+    );
+
+    // Validate coupon is still usable (active, unexpired, uses left)
+    let current_time = Clock::get()?.unix_timestamp;
+    ctx.accounts.coupon_data.require_tradeable(current_time)?;
+    require!(
+        current_time < ctx.accounts.listing.expires_at,
+        CouponError::ListingExpired
+    );
 
     // Calculate payment splits
     let platform_fee = (price_lamports
@@ -154,5 +198,17 @@ pub fn handler(ctx: Context<PurchaseFromResale>, price_lamports: u64) -> Result<
         platform_fee
     );
 
+    emit!(CouponTradedEvent {
+        nft_mint: ctx.accounts.nft_mint.key(),
+        seller: ctx.accounts.seller.key(),
+        buyer: ctx.accounts.buyer.key(),
+        price: price_lamports,
+        platform_fee,
+        uses_left: ctx.accounts.coupon_data.uses_left(),
+        balance_remaining: ctx.accounts.coupon_data.balance,
+        expiry_date: ctx.accounts.coupon_data.expiry_date,
+        timestamp: current_time,
+    });
+
     Ok(())
 }
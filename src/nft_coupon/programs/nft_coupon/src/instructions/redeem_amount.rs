@@ -0,0 +1,168 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{burn, Burn, Mint, Token, TokenAccount};
+use crate::state::*;
+use crate::errors::*;
+use super::redeem_coupon::RedemptionEvent;
+
+/// Redeem part of a stored-value (gift card) coupon
+///
+/// Deducts `amount` from the coupon balance and burns the NFT
+/// once the balance reaches zero.
+#[derive(Accounts)]
+pub struct RedeemAmount<'info> {
+    /// Coupon data account (PDA derived from NFT mint)
+    /// Seeds: ["coupon", nft_mint_pubkey]
+    /// Tracks remaining balance and active status
+    #[account(
+        mut,
+        seeds = [b"coupon", nft_mint.key().as_ref()],
+        bump = coupon_data.bump,
+        has_one = merchant @ CouponError::UnauthorizedMerchant,
+    )]
+    pub coupon_data: Account<'info, CouponData>,
+
+    /// Merchant account (PDA derived from merchant authority)
+    /// Seeds: ["merchant", seed_authority_pubkey]
+    /// Used for event logging and analytics (redemption counters are updated)
+    /// Redemptions are blocked while the merchant is suspended
+    #[account(
+        mut,
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        constraint = !merchant.is_suspended @ CouponError::MerchantSuspended,
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    /// NFT mint account (must match coupon_data.mint)
+    #[account(mut)]
+    pub nft_mint: Account<'info, Mint>,
+
+    /// User's token account holding the NFT
+    /// Validates ownership via mint and owner constraints
+    #[account(
+        mut,
+        constraint = nft_token_account.mint == nft_mint.key(),
+        constraint = nft_token_account.owner == user.key()
+    )]
+    pub nft_token_account: Account<'info, TokenAccount>,
+
+    /// User redeeming the coupon (must own the NFT)
+    /// Pays for the redeemer record only if they never claimed/bought from this merchant
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// Per-wallet redemption record for unique redeemer stats
+    /// Seeds: ["redeemer", merchant, user]
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = RedeemerRecord::LEN,
+        seeds = [b"redeemer", merchant.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub redeemer_record: Account<'info, RedeemerRecord>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<RedeemAmount>, amount: u64) -> Result<()> {
+    let coupon_data = &mut ctx.accounts.coupon_data;
+
+    // Security checks: Validate coupon state before redemption
+
+    // 1. Coupon must be an active stored-value coupon
+    require!(coupon_data.is_active, CouponError::CouponNotActive);
+    require!(
+        matches!(coupon_data.discount_kind, DiscountKind::StoredValue { .. }),
+        CouponError::NotStoredValueCoupon
+    );
+
+    // 2. Coupon must not be expired (checked against on-chain clock)
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(
+        coupon_data.expiry_date > current_time,
+        CouponError::CouponExpired
+    );
+
+    // 3. Redemption window must be open (may differ from the sale window)
+    coupon_data.require_redemption_open(current_time)?;
+    if let Some(schedule) = coupon_data.redemption_schedule {
+        require!(
+            schedule.is_open(current_time),
+            CouponError::OutsideRedemptionHours
+        );
+    }
+
+    // 4. Amount must be covered by the remaining balance
+    require!(amount > 0, CouponError::InvalidRedemptionAmount);
+    require!(
+        amount <= coupon_data.balance,
+        CouponError::InsufficientBalance
+    );
+
+    // 5. Merchant's cooldown applies between partial redemptions too
+    coupon_data.require_cooldown_elapsed(current_time)?;
+
+    // 6. User must own the NFT (verified by token account constraints + amount check)
+    require!(
+        ctx.accounts.nft_token_account.amount >= 1,
+        CouponError::UnauthorizedOwner
+    );
+
+    // Deduct balance atomically
+    coupon_data.balance = coupon_data
+        .balance
+        .checked_sub(amount)
+        .ok_or(CouponError::ArithmeticOverflow)?;
+    coupon_data.last_redeemed_at = current_time;
+    coupon_data.release_on_redemption(current_time); // Deferred proceeds no longer held back
+
+    // Merchant analytics: total and unique redeemer counters
+    let merchant_key = ctx.accounts.merchant.key();
+    let redeemer_record = &mut ctx.accounts.redeemer_record;
+    redeemer_record.init(ctx.accounts.user.key(), merchant_key, ctx.bumps.redeemer_record);
+    let first_for_wallet = redeemer_record.record(current_time)?;
+    ctx.accounts.merchant.record_redemption(first_for_wallet)?;
+
+    if coupon_data.balance == 0 {
+        // Balance exhausted: burn the NFT so it can't be presented again
+        coupon_data.redemptions_remaining = 0;
+        burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.nft_mint.to_account_info(),
+                    from: ctx.accounts.nft_token_account.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            1, // Burn 1 NFT
+        )?;
+
+        msg!(
+            "Stored-value coupon {} fully spent and NFT burned",
+            ctx.accounts.nft_mint.key()
+        );
+    } else {
+        msg!(
+            "Stored-value coupon {} redeemed: {} spent, {} remaining",
+            ctx.accounts.nft_mint.key(),
+            amount,
+            coupon_data.balance
+        );
+    }
+
+    emit!(RedemptionEvent {
+        nft_mint: ctx.accounts.nft_mint.key(),
+        merchant: coupon_data.merchant,
+        user: ctx.accounts.user.key(),
+        uses_left: coupon_data.uses_left(),
+        discount_kind: coupon_data.discount_kind,
+        amount,
+        balance_remaining: coupon_data.balance,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
@@ -13,16 +13,20 @@ pub struct RedeemCoupon<'info> {
     #[account(
         mut,
         seeds = [b"coupon", nft_mint.key().as_ref()],
-        bump = coupon_data.bump
+        bump = coupon_data.bump,
+        has_one = merchant @ CouponError::UnauthorizedMerchant,
     )]
     pub coupon_data: Account<'info, CouponData>,
 
     /// Merchant account (PDA derived from merchant authority)
-    /// Seeds: ["merchant", merchant_authority_pubkey]
-    /// Used for event logging and analytics
+    /// Seeds: ["merchant", seed_authority_pubkey]
+    /// Used for event logging and analytics (redemption counters are updated)
+    /// Redemptions are blocked while the merchant is suspended
     #[account(
-        seeds = [b"merchant", merchant.authority.as_ref()],
-        bump = merchant.bump
+        mut,
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        constraint = !merchant.is_suspended @ CouponError::MerchantSuspended,
     )]
     pub merchant: Account<'info, Merchant>,
 
@@ -40,10 +44,23 @@ pub struct RedeemCoupon<'info> {
     pub nft_token_account: Account<'info, TokenAccount>,
 
     /// User redeeming the coupon (must own the NFT)
+    /// Pays for the redeemer record only if they never claimed/bought from this merchant
     #[account(mut)]
     pub user: Signer<'info>,
 
+    /// Per-wallet redemption record for unique redeemer stats
+    /// Seeds: ["redeemer", merchant, user]
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = RedeemerRecord::LEN,
+        seeds = [b"redeemer", merchant.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub redeemer_record: Account<'info, RedeemerRecord>,
+
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 pub fn handler(ctx: Context<RedeemCoupon>) -> Result<()> {
@@ -54,6 +71,12 @@ pub fn handler(ctx: Context<RedeemCoupon>) -> Result<()> {
     // 1. Coupon must be active (merchant can deactivate via update_coupon_status)
     require!(coupon_data.is_active, CouponError::CouponNotActive);
 
+    // Stored-value (gift card) coupons are spent by amount, not by use
+    require!(
+        !matches!(coupon_data.discount_kind, DiscountKind::StoredValue { .. }),
+        CouponError::UseRedeemAmount
+    );
+
     // 2. Coupon must not be expired (checked against on-chain clock)
     let current_time = Clock::get()?.unix_timestamp;
     require!(
@@ -61,13 +84,25 @@ pub fn handler(ctx: Context<RedeemCoupon>) -> Result<()> {
         CouponError::CouponExpired
     );
 
-    // 3. Coupon must have redemptions remaining (prevents double-spend)
+    // 3. Redemption window must be open (may differ from the sale window)
+    coupon_data.require_redemption_open(current_time)?;
+    if let Some(schedule) = coupon_data.redemption_schedule {
+        require!(
+            schedule.is_open(current_time),
+            CouponError::OutsideRedemptionHours
+        );
+    }
+
+    // 4. Coupon must have redemptions remaining (prevents double-spend)
     require!(
         coupon_data.redemptions_remaining > 0,
         CouponError::CouponFullyRedeemed
     );
 
-    // 4. User must own the NFT (verified by token account constraints + amount check)
+    // 5. Multi-use coupons must respect the merchant's cooldown (prevents back-to-back burns)
+    coupon_data.require_cooldown_elapsed(current_time)?;
+
+    // 6. User must own the NFT (verified by token account constraints + amount check)
     require!(
         ctx.accounts.nft_token_account.amount >= 1,
         CouponError::UnauthorizedOwner
@@ -78,6 +113,15 @@ pub fn handler(ctx: Context<RedeemCoupon>) -> Result<()> {
         .redemptions_remaining
         .checked_sub(1)
         .ok_or(CouponError::ArithmeticOverflow)?;
+    coupon_data.last_redeemed_at = current_time;
+    coupon_data.release_on_redemption(current_time); // Deferred proceeds no longer held back
+
+    // Merchant analytics: total and unique redeemer counters
+    let merchant_key = ctx.accounts.merchant.key();
+    let redeemer_record = &mut ctx.accounts.redeemer_record;
+    redeemer_record.init(ctx.accounts.user.key(), merchant_key, ctx.bumps.redeemer_record);
+    let first_for_wallet = redeemer_record.record(current_time)?;
+    ctx.accounts.merchant.record_redemption(first_for_wallet)?;
 
     // Multi-use coupon support (bonus feature beyond requirements):
     // - Single-use (max_redemptions=1): Burn NFT immediately
@@ -117,7 +161,10 @@ pub fn handler(ctx: Context<RedeemCoupon>) -> Result<()> {
         nft_mint: ctx.accounts.nft_mint.key(),
         merchant: coupon_data.merchant,
         user: ctx.accounts.user.key(),
-        redemptions_remaining: coupon_data.redemptions_remaining,
+        uses_left: coupon_data.uses_left(),
+        discount_kind: coupon_data.discount_kind,
+        amount: 0,
+        balance_remaining: coupon_data.balance,
         timestamp: current_time,
     });
 
@@ -129,6 +176,14 @@ pub struct RedemptionEvent {
     pub nft_mint: Pubkey,
     pub merchant: Pubkey,
     pub user: Pubkey,
-    pub redemptions_remaining: u8,
+    /// Redemptions the holder can still make after this one (`CouponData::uses_left`,
+    /// 0 for stored value - see `balance_remaining`)
+    pub uses_left: u8,
+    /// Tells POS integrations which discount to apply
+    pub discount_kind: DiscountKind,
+    /// Stored value spent by this redemption (0 for use-count redemptions)
+    pub amount: u64,
+    /// Stored value left after this redemption (0 for use-count coupons)
+    pub balance_remaining: u64,
     pub timestamp: i64,
 }
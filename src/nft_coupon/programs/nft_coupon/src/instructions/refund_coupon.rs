@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{burn, Burn, Mint, Token, TokenAccount};
+use crate::state::*;
+use crate::errors::*;
+
+/// Refund a paid coupon the merchant cancelled before honoring it
+///
+/// Requirements:
+/// - Coupon cancelled via update_coupon_status(false) before it was ever redeemed
+/// - Price paid escrowed in the merchant's RevenueVault (at sale time under deferred
+///   settlement, or deposited by the merchant on cancellation for direct-mode sales)
+/// - Caller holds the NFT (the original purchaser or whoever it was resold to)
+///
+/// The NFT is burned and the escrowed price returned to the holder.
+/// Works while the merchant is suspended - that is when holders need it most.
+#[derive(Accounts)]
+pub struct RefundCoupon<'info> {
+    /// Coupon data account (PDA derived from NFT mint)
+    #[account(
+        mut,
+        seeds = [b"coupon", nft_mint.key().as_ref()],
+        bump = coupon_data.bump,
+        has_one = merchant @ CouponError::UnauthorizedMerchant,
+        constraint = coupon_data.is_refundable() @ CouponError::NotRefundable,
+    )]
+    pub coupon_data: Account<'info, CouponData>,
+
+    /// Merchant account (PDA derived from merchant authority)
+    #[account(
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    /// Merchant revenue vault holding the purchase price
+    /// Seeds: ["revenue_vault", merchant]
+    #[account(
+        mut,
+        seeds = [b"revenue_vault", merchant.key().as_ref()],
+        bump = revenue_vault.bump,
+    )]
+    pub revenue_vault: Account<'info, RevenueVault>,
+
+    /// NFT mint account (must match coupon_data.mint)
+    #[account(mut)]
+    pub nft_mint: Account<'info, Mint>,
+
+    /// Holder's token account holding the NFT
+    #[account(
+        mut,
+        constraint = holder_token_account.mint == nft_mint.key(),
+        constraint = holder_token_account.owner == holder.key(),
+        constraint = holder_token_account.amount == 1 @ CouponError::UnauthorizedOwner,
+    )]
+    pub holder_token_account: Account<'info, TokenAccount>,
+
+    /// Current NFT holder - receives the refund
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<RefundCoupon>) -> Result<()> {
+    // Burn the NFT so the cancelled coupon can't be traded or presented
+    burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.nft_mint.to_account_info(),
+                from: ctx.accounts.holder_token_account.to_account_info(),
+                authority: ctx.accounts.holder.to_account_info(),
+            },
+        ),
+        1,
+    )?;
+
+    let coupon_data = &mut ctx.accounts.coupon_data;
+    let refund = coupon_data.escrowed_lamports;
+    coupon_data.escrowed_lamports = 0;
+    coupon_data.redemptions_remaining = 0;
+
+    let revenue_vault = &mut ctx.accounts.revenue_vault;
+    revenue_vault.escrowed_total = revenue_vault
+        .escrowed_total
+        .checked_sub(refund)
+        .ok_or(CouponError::ArithmeticOverflow)?;
+
+    // Vault is program-owned: move lamports directly
+    revenue_vault.sub_lamports(refund)?;
+    ctx.accounts.holder.add_lamports(refund)?;
+
+    msg!(
+        "Coupon {} refunded: {} lamports returned to {}",
+        ctx.accounts.nft_mint.key(),
+        refund,
+        ctx.accounts.holder.key()
+    );
+
+    emit!(RefundEvent {
+        nft_mint: ctx.accounts.nft_mint.key(),
+        merchant: ctx.accounts.merchant.key(),
+        holder: ctx.accounts.holder.key(),
+        amount: refund,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct RefundEvent {
+    pub nft_mint: Pubkey,
+    pub merchant: Pubkey,
+    pub holder: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
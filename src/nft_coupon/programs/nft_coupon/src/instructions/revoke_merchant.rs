@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+
+#[derive(Accounts)]
+pub struct RevokeMerchant<'info> {
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = admin @ CouponError::UnauthorizedAdmin
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<RevokeMerchant>) -> Result<()> {
+    let merchant = &mut ctx.accounts.merchant;
+    merchant.verification_status = VerificationStatus::Revoked;
+    merchant.verifier = ctx.accounts.admin.key();
+
+    msg!(
+        "Merchant revoked: {} ({}) by {}",
+        merchant.business_name,
+        merchant.key(),
+        merchant.verifier
+    );
+
+    Ok(())
+}
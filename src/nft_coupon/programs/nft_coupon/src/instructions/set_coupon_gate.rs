@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use crate::state::*;
+use crate::errors::*;
+
+#[derive(Accounts)]
+pub struct SetCouponGate<'info> {
+    #[account(
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        has_one = authority @ CouponError::UnauthorizedMerchant,
+        constraint = merchant.authority == merchant_authority.key() @ CouponError::UnauthorizedMerchant
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    #[account(
+        mut,
+        seeds = [b"coupon", coupon_data.mint.as_ref()],
+        bump = coupon_data.bump,
+        constraint = coupon_data.merchant == merchant.key() @ CouponError::UnauthorizedMerchant
+    )]
+    pub coupon_data: Account<'info, CouponData>,
+
+    /// NFT Escrow PDA - the gate only governs who takes the NFT out of escrow,
+    /// so it can't change once the coupon has a holder
+    /// Seeds: ["nft_escrow", merchant_pda, nft_mint]
+    #[account(
+        seeds = [b"nft_escrow", merchant.key().as_ref(), coupon_data.mint.as_ref()],
+        bump,
+        token::authority = merchant,
+        constraint = nft_escrow.amount == 1 @ CouponError::GateLocked,
+    )]
+    pub nft_escrow: Account<'info, TokenAccount>,
+
+    /// CHECK: Merchant authority verified via merchant account
+    pub authority: UncheckedAccount<'info>,
+
+    pub merchant_authority: Signer<'info>,
+}
+
+pub fn handler(
+    ctx: Context<SetCouponGate>,
+    gate_kind: GateKind,
+    gate_mint: Pubkey,
+    gate_min_amount: u64,
+) -> Result<()> {
+    // A gate needs a mint (token or collection) and no gate must not carry one
+    let gated = gate_kind != GateKind::None;
+    require!(
+        gated == (gate_mint != Pubkey::default()),
+        CouponError::InvalidGateConfig
+    );
+    require!(
+        gate_kind != GateKind::TokenBalance || gate_min_amount > 0,
+        CouponError::InvalidGateConfig
+    );
+    require!(gated || gate_min_amount == 0, CouponError::InvalidGateConfig);
+
+    let coupon_data = &mut ctx.accounts.coupon_data;
+
+    coupon_data.gate_kind = gate_kind;
+    coupon_data.gate_mint = gate_mint;
+    coupon_data.gate_min_amount = gate_min_amount;
+
+    msg!(
+        "Coupon {} token gate updated: mint {} | min amount {}",
+        coupon_data.mint,
+        gate_mint,
+        gate_min_amount
+    );
+
+    Ok(())
+}
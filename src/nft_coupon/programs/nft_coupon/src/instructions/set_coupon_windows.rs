@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+
+#[derive(Accounts)]
+pub struct SetCouponWindows<'info> {
+    #[account(
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        has_one = authority @ CouponError::UnauthorizedMerchant,
+        constraint = merchant.authority == merchant_authority.key() @ CouponError::UnauthorizedMerchant
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    #[account(
+        mut,
+        seeds = [b"coupon", coupon_data.mint.as_ref()],
+        bump = coupon_data.bump,
+        constraint = coupon_data.merchant == merchant.key() @ CouponError::UnauthorizedMerchant
+    )]
+    pub coupon_data: Account<'info, CouponData>,
+
+    /// CHECK: Merchant authority verified via merchant account
+    pub authority: UncheckedAccount<'info>,
+
+    pub merchant_authority: Signer<'info>,
+}
+
+/// A window bound of 0 means "unbounded" on that side (expiry_date still applies)
+fn validate_window(start: i64, end: i64, expiry_date: i64) -> Result<()> {
+    require!(
+        start >= 0 && end >= 0,
+        CouponError::InvalidTimeWindow
+    );
+    if end != 0 {
+        require!(
+            start < end && end <= expiry_date,
+            CouponError::InvalidTimeWindow
+        );
+    }
+    require!(start < expiry_date, CouponError::InvalidTimeWindow);
+    Ok(())
+}
+
+pub fn handler(
+    ctx: Context<SetCouponWindows>,
+    sale_start: i64,
+    sale_end: i64,
+    redeem_start: i64,
+    redeem_end: i64,
+) -> Result<()> {
+    let coupon_data = &mut ctx.accounts.coupon_data;
+
+    validate_window(sale_start, sale_end, coupon_data.expiry_date)?;
+    validate_window(redeem_start, redeem_end, coupon_data.expiry_date)?;
+
+    coupon_data.sale_start = sale_start;
+    coupon_data.sale_end = sale_end;
+    coupon_data.redeem_start = redeem_start;
+    coupon_data.redeem_end = redeem_end;
+
+    msg!(
+        "Coupon {} windows updated: sale [{}, {}) | redeem [{}, {})",
+        coupon_data.mint,
+        sale_start,
+        sale_end,
+        redeem_start,
+        redeem_end
+    );
+
+    Ok(())
+}
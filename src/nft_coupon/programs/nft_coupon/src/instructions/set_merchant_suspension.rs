@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+
+/// Suspend or reinstate a merchant (platform admin only)
+///
+/// While suspended, every claim, purchase, redeem and resale of the
+/// merchant's coupons is rejected via the Merchant account check.
+#[derive(Accounts)]
+pub struct SetMerchantSuspension<'info> {
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = admin @ CouponError::UnauthorizedAdmin
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(
+    ctx: Context<SetMerchantSuspension>,
+    is_suspended: bool,
+) -> Result<()> {
+    let merchant = &mut ctx.accounts.merchant;
+    merchant.is_suspended = is_suspended;
+
+    msg!(
+        "Merchant {} ({}) {}",
+        merchant.business_name,
+        merchant.key(),
+        if is_suspended { "suspended" } else { "reinstated" }
+    );
+
+    Ok(())
+}
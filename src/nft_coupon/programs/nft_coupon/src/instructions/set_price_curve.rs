@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+
+#[derive(Accounts)]
+pub struct SetPriceCurve<'info> {
+    #[account(
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        has_one = authority @ CouponError::UnauthorizedMerchant,
+        constraint = merchant.authority == merchant_authority.key() @ CouponError::UnauthorizedMerchant
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    #[account(
+        mut,
+        seeds = [b"coupon", coupon_data.mint.as_ref()],
+        bump = coupon_data.bump,
+        constraint = coupon_data.merchant == merchant.key() @ CouponError::UnauthorizedMerchant,
+        constraint = coupon_data.price > 0 @ CouponError::NotPaidCoupon
+    )]
+    pub coupon_data: Account<'info, CouponData>,
+
+    /// CHECK: Merchant authority verified via merchant account
+    pub authority: UncheckedAccount<'info>,
+
+    pub merchant_authority: Signer<'info>,
+}
+
+pub fn handler(
+    ctx: Context<SetPriceCurve>,
+    price_curve: Option<PriceCurve>,
+) -> Result<()> {
+    if let Some(curve) = &price_curve {
+        require!(curve.is_valid(), CouponError::InvalidPriceCurve);
+    }
+
+    let coupon_data = &mut ctx.accounts.coupon_data;
+
+    coupon_data.price_curve = price_curve;
+
+    match &price_curve {
+        Some(curve) => msg!(
+            "Coupon {} price curve set: {} -> {} lamports",
+            coupon_data.mint,
+            curve.start_price,
+            curve.end_price
+        ),
+        None => msg!("Coupon {} price curve cleared", coupon_data.mint),
+    }
+
+    Ok(())
+}
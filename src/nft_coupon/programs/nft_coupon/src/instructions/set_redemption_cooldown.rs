@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+
+#[derive(Accounts)]
+pub struct SetRedemptionCooldown<'info> {
+    #[account(
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        has_one = authority @ CouponError::UnauthorizedMerchant,
+        constraint = merchant.authority == merchant_authority.key() @ CouponError::UnauthorizedMerchant
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    #[account(
+        mut,
+        seeds = [b"coupon", coupon_data.mint.as_ref()],
+        bump = coupon_data.bump,
+        constraint = coupon_data.merchant == merchant.key() @ CouponError::UnauthorizedMerchant
+    )]
+    pub coupon_data: Account<'info, CouponData>,
+
+    /// CHECK: Merchant authority verified via merchant account
+    pub authority: UncheckedAccount<'info>,
+
+    pub merchant_authority: Signer<'info>,
+}
+
+pub fn handler(
+    ctx: Context<SetRedemptionCooldown>,
+    min_seconds_between_redemptions: i64,
+) -> Result<()> {
+    require!(
+        min_seconds_between_redemptions >= 0,
+        CouponError::InvalidCooldown
+    );
+
+    let coupon_data = &mut ctx.accounts.coupon_data;
+
+    coupon_data.min_seconds_between_redemptions = min_seconds_between_redemptions;
+
+    msg!(
+        "Coupon {} redemption cooldown set: {}s",
+        coupon_data.mint,
+        min_seconds_between_redemptions
+    );
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+
+#[derive(Accounts)]
+pub struct SetRedemptionSchedule<'info> {
+    #[account(
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        has_one = authority @ CouponError::UnauthorizedMerchant,
+        constraint = merchant.authority == merchant_authority.key() @ CouponError::UnauthorizedMerchant
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    #[account(
+        mut,
+        seeds = [b"coupon", coupon_data.mint.as_ref()],
+        bump = coupon_data.bump,
+        constraint = coupon_data.merchant == merchant.key() @ CouponError::UnauthorizedMerchant
+    )]
+    pub coupon_data: Account<'info, CouponData>,
+
+    /// CHECK: Merchant authority verified via merchant account
+    pub authority: UncheckedAccount<'info>,
+
+    pub merchant_authority: Signer<'info>,
+}
+
+pub fn handler(
+    ctx: Context<SetRedemptionSchedule>,
+    schedule: Option<RedemptionSchedule>,
+) -> Result<()> {
+    if let Some(schedule) = &schedule {
+        require!(schedule.is_valid(), CouponError::InvalidRedemptionSchedule);
+    }
+
+    let coupon_data = &mut ctx.accounts.coupon_data;
+
+    coupon_data.redemption_schedule = schedule;
+
+    msg!(
+        "Coupon {} redemption schedule {}",
+        coupon_data.mint,
+        if schedule.is_some() { "set" } else { "cleared" }
+    );
+
+    Ok(())
+}
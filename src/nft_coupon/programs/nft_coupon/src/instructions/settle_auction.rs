@@ -0,0 +1,249 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
+use crate::state::*;
+use crate::errors::*;
+
+/// Settle an auction after it ends (permissionless)
+///
+/// With a winning bid:
+/// 1. NFT transferred from the Auction Escrow PDA to the winner
+/// 2. Top bid split 97.5% to the seller, 2.5% to the platform
+///    (primary sales of deferred-settlement merchants go to the RevenueVault instead)
+///
+/// Without bids the NFT goes back to the seller (or the merchant's Escrow PDA).
+/// The auction accounts are closed and their rent returned to the seller.
+#[derive(Accounts)]
+pub struct SettleAuction<'info> {
+    /// Auction PDA - closed, rent returned to seller
+    /// Seeds: ["auction", nft_mint]
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"auction", nft_mint.key().as_ref()],
+        bump = auction.bump,
+        has_one = merchant @ CouponError::UnauthorizedMerchant,
+        has_one = seller,
+    )]
+    pub auction: Account<'info, Auction>,
+
+    /// Auction Escrow PDA - closed after the NFT leaves
+    /// Seeds: ["auction_escrow", nft_mint]
+    #[account(
+        mut,
+        seeds = [b"auction_escrow", nft_mint.key().as_ref()],
+        bump,
+        token::mint = nft_mint,
+        token::authority = auction,
+    )]
+    pub auction_escrow: Account<'info, TokenAccount>,
+
+    /// NFT mint account
+    pub nft_mint: Account<'info, Mint>,
+
+    /// Coupon data account (updated for primary sales)
+    #[account(
+        mut,
+        seeds = [b"coupon", nft_mint.key().as_ref()],
+        bump = coupon_data.bump,
+        has_one = merchant @ CouponError::UnauthorizedMerchant,
+    )]
+    pub coupon_data: Account<'info, CouponData>,
+
+    /// Merchant account (sales stats updated for primary sales)
+    /// Not blocked by suspension so bidders are never locked in
+    #[account(
+        mut,
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    /// Token account receiving the NFT:
+    /// - Winner's token account when there is a winning bid
+    /// - Otherwise the seller's token account (resale) or the merchant's Escrow PDA (primary)
+    #[account(
+        mut,
+        token::mint = nft_mint,
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    /// Seller - receives proceeds (minus fee) and the auction accounts' rent
+    /// CHECK: Validated by auction.seller
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+
+    /// Platform config singleton (fee wallet)
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// Platform fee wallet - receives 2.5% of the winning bid
+    /// CHECK: Must be the fee wallet stored in the platform config
+    #[account(
+        mut,
+        address = platform_config.fee_wallet @ CouponError::InvalidFeeWallet,
+    )]
+    pub platform_wallet: UncheckedAccount<'info>,
+
+    /// Merchant revenue vault - required for primary sales of deferred-settlement merchants
+    /// Seeds: ["revenue_vault", merchant]
+    #[account(
+        mut,
+        seeds = [b"revenue_vault", merchant.key().as_ref()],
+        bump = revenue_vault.bump,
+    )]
+    pub revenue_vault: Option<Account<'info, RevenueVault>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<SettleAuction>) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let auction = &ctx.accounts.auction;
+    require!(current_time >= auction.end_time, CouponError::AuctionNotEnded);
+
+    let winning_bid = auction.highest_bid;
+    let is_primary = auction.is_primary;
+    let winner = auction.highest_bidder;
+
+    // 1. NFT goes to the winner, or back where it came from without bids
+    let recipient_owner = ctx.accounts.recipient_token_account.owner;
+    let expected_owner = if winning_bid > 0 {
+        winner
+    } else if is_primary {
+        auction.merchant
+    } else {
+        auction.seller
+    };
+    require_keys_eq!(recipient_owner, expected_owner, CouponError::InvalidAuctionRecipient);
+    if winning_bid == 0 && is_primary {
+        // Unsold primary NFTs must go back to the merchant's Escrow PDA itself, not just
+        // any token account the merchant PDA happens to own
+        let nft_mint_key = ctx.accounts.nft_mint.key();
+        let (nft_escrow, _) = Pubkey::find_program_address(
+            &[b"nft_escrow", auction.merchant.as_ref(), nft_mint_key.as_ref()],
+            ctx.program_id,
+        );
+        require_keys_eq!(
+            ctx.accounts.recipient_token_account.key(),
+            nft_escrow,
+            CouponError::InvalidAuctionRecipient
+        );
+    }
+
+    if winning_bid > 0 {
+        // 2. Calculate payment splits
+        let platform_fee = (winning_bid
+            .checked_mul(25)
+            .ok_or(CouponError::ArithmeticOverflow)?)
+            .checked_div(1000)
+            .ok_or(CouponError::ArithmeticOverflow)?; // 2.5%
+        let seller_amount = winning_bid
+            .checked_sub(platform_fee)
+            .ok_or(CouponError::ArithmeticOverflow)?; // 97.5%
+
+        // 3. Pay out the winning bid (Auction PDA is program-owned)
+        ctx.accounts.auction.sub_lamports(winning_bid)?;
+        if is_primary && ctx.accounts.merchant.deferred_settlement {
+            // Deferred settlement: full price held in the RevenueVault, as in purchase_coupon
+            let revenue_vault = ctx
+                .accounts
+                .revenue_vault
+                .as_mut()
+                .ok_or(CouponError::MissingRevenueVault)?;
+            revenue_vault.add_lamports(winning_bid)?;
+            revenue_vault.escrowed_total = revenue_vault
+                .escrowed_total
+                .checked_add(winning_bid)
+                .ok_or(CouponError::ArithmeticOverflow)?;
+
+            let coupon_data = &mut ctx.accounts.coupon_data;
+            coupon_data.escrowed_lamports = winning_bid;
+            coupon_data.settle_after = current_time
+                .checked_add(ctx.accounts.merchant.holdback_seconds)
+                .ok_or(CouponError::ArithmeticOverflow)?;
+        } else {
+            ctx.accounts.seller.add_lamports(seller_amount)?;
+            if platform_fee > 0 {
+                ctx.accounts.platform_wallet.add_lamports(platform_fee)?;
+            }
+        }
+
+        // 4. Primary sales update the coupon and merchant like purchase_coupon
+        if is_primary {
+            let coupon_data = &mut ctx.accounts.coupon_data;
+            coupon_data.purchaser = winner;
+            coupon_data.paid_lamports = winning_bid; // Refund amount if the merchant cancels
+            coupon_data.redemptions_remaining = coupon_data
+                .redemptions_remaining
+                .checked_sub(1)
+                .ok_or(CouponError::ArithmeticOverflow)?;
+            ctx.accounts.merchant.record_sale(winning_bid, platform_fee)?;
+        }
+
+        msg!(
+            "🎉 Auction settled! NFT {} | Winner: {} | Price: {} lamports | Fee: {}",
+            ctx.accounts.nft_mint.key(),
+            winner,
+            winning_bid,
+            platform_fee
+        );
+    } else {
+        msg!(
+            "Auction for {} ended without bids - NFT returned",
+            ctx.accounts.nft_mint.key()
+        );
+    }
+
+    // 5. Release the NFT and close the Auction Escrow PDA
+    let nft_mint_key = ctx.accounts.nft_mint.key();
+    let auction_bump = ctx.accounts.auction.bump;
+    let seeds = &[b"auction".as_ref(), nft_mint_key.as_ref(), &[auction_bump]];
+    let signer = &[&seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.auction_escrow.to_account_info(),
+                to: ctx.accounts.recipient_token_account.to_account_info(),
+                authority: ctx.accounts.auction.to_account_info(),
+            },
+            signer,
+        ),
+        1,
+    )?;
+    token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.auction_escrow.to_account_info(),
+            destination: ctx.accounts.seller.to_account_info(),
+            authority: ctx.accounts.auction.to_account_info(),
+        },
+        signer,
+    ))?;
+
+    emit!(AuctionSettledEvent {
+        nft_mint: nft_mint_key,
+        seller: ctx.accounts.seller.key(),
+        winner,
+        price: winning_bid,
+        is_primary,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct AuctionSettledEvent {
+    pub nft_mint: Pubkey,
+    pub seller: Pubkey,
+    /// Default pubkey when the auction ended without bids
+    pub winner: Pubkey,
+    pub price: u64,
+    pub is_primary: bool,
+    pub timestamp: i64,
+}
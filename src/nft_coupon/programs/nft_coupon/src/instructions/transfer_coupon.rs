@@ -5,6 +5,7 @@ use anchor_spl::{
     associated_token::AssociatedToken,
     token::{self, Mint, Token, TokenAccount, Transfer},
 };
+use crate::state::*;
 use crate::errors::*;
 
 /// Peer-to-peer NFT coupon transfer with payment
@@ -13,6 +14,7 @@ use crate::errors::*;
 /// - Buyer pays SOL (97.5% to seller, 2.5% to platform)
 /// - Seller transfers NFT to buyer
 /// - All happens atomically in ONE transaction
+/// - Expired, inactive or used-up coupons can't be traded
 ///
 /// NO ESCROW PDA REQUIRED - Direct P2P atomic swap
 #[derive(Accounts)]
@@ -20,6 +22,23 @@ pub struct TransferCoupon<'info> {
     /// NFT mint account
     pub nft_mint: Account<'info, Mint>,
 
+    /// Coupon data account (PDA derived from NFT mint address)
+    /// Links the traded NFT to its merchant and gates trading on its status
+    #[account(
+        seeds = [b"coupon", nft_mint.key().as_ref()],
+        bump = coupon_data.bump,
+        has_one = merchant @ CouponError::UnauthorizedMerchant,
+    )]
+    pub coupon_data: Account<'info, CouponData>,
+
+    /// Merchant account - resales are blocked while the merchant is suspended
+    #[account(
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        constraint = !merchant.is_suspended @ CouponError::MerchantSuspended,
+    )]
+    pub merchant: Account<'info, Merchant>,
+
     /// Seller's token account (source) - must have amount = 1 (owns NFT)
     #[account(
         mut,
@@ -61,6 +80,10 @@ pub fn handler(ctx: Context<TransferCoupon>, price_lamports: u64) -> Result<()>
     // Validate price
     require!(price_lamports > 0, CouponError::InvalidPrice);
 
+    // Validate coupon is still usable (active, unexpired, uses left)
+    let current_time = Clock::get()?.unix_timestamp;
+    ctx.accounts.coupon_data.require_tradeable(current_time)?;
+
     // Calculate payment splits
     let platform_fee = (price_lamports
         .checked_mul(25)
@@ -138,5 +161,33 @@ pub fn handler(ctx: Context<TransferCoupon>, price_lamports: u64) -> Result<()>
         platform_fee
     );
 
+    emit!(CouponTradedEvent {
+        nft_mint: ctx.accounts.nft_mint.key(),
+        seller: ctx.accounts.seller.key(),
+        buyer: ctx.accounts.buyer.key(),
+        price: price_lamports,
+        platform_fee,
+        uses_left: ctx.accounts.coupon_data.uses_left(),
+        balance_remaining: ctx.accounts.coupon_data.balance,
+        expiry_date: ctx.accounts.coupon_data.expiry_date,
+        timestamp: current_time,
+    });
+
     Ok(())
 }
+
+/// Emitted on every secondary-market trade so buyers and indexers see what was bought
+#[event]
+pub struct CouponTradedEvent {
+    pub nft_mint: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub price: u64,
+    pub platform_fee: u64,
+    /// Redemptions the buyer can make (`CouponData::uses_left`, 0 for stored value)
+    pub uses_left: u8,
+    /// Stored value left at trade time (0 for use-count coupons)
+    pub balance_remaining: u64,
+    pub expiry_date: i64,
+    pub timestamp: i64,
+}
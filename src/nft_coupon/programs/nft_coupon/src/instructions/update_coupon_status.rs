@@ -1,13 +1,22 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::system_instruction;
 use crate::state::*;
 use crate::errors::*;
 
+/// Activate/deactivate a coupon
+///
+/// Deactivating a sold, never-redeemed coupon cancels it with a refund owed.
+/// The price paid is escrowed in the merchant's RevenueVault; for direct-mode
+/// sales the merchant authority deposits it here (open the vault with
+/// configure_settlement first). The holder then claims it with refund_coupon.
 #[derive(Accounts)]
 pub struct UpdateCouponStatus<'info> {
     #[account(
-        seeds = [b"merchant", merchant_authority.key().as_ref()],
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
         bump = merchant.bump,
-        has_one = authority @ CouponError::UnauthorizedMerchant
+        has_one = authority @ CouponError::UnauthorizedMerchant,
+        constraint = merchant.authority == merchant_authority.key() @ CouponError::UnauthorizedMerchant
     )]
     pub merchant: Account<'info, Merchant>,
 
@@ -19,10 +28,23 @@ pub struct UpdateCouponStatus<'info> {
     )]
     pub coupon_data: Account<'info, CouponData>,
 
+    /// Merchant revenue vault - only needed to cancel a sold coupon
+    /// Seeds: ["revenue_vault", merchant]
+    #[account(
+        mut,
+        seeds = [b"revenue_vault", merchant.key().as_ref()],
+        bump = revenue_vault.bump,
+    )]
+    pub revenue_vault: Option<Account<'info, RevenueVault>>,
+
     /// CHECK: Merchant authority verified via merchant account
     pub authority: UncheckedAccount<'info>,
 
+    /// Pays any refund not already escrowed
+    #[account(mut)]
     pub merchant_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 pub fn handler(
@@ -31,7 +53,12 @@ pub fn handler(
 ) -> Result<()> {
     let coupon_data = &mut ctx.accounts.coupon_data;
 
-    coupon_data.is_active = is_active;
+    let refund_shortfall = coupon_data.set_active(is_active)?;
+    escrow_refunds(
+        ctx.accounts.revenue_vault.as_mut(),
+        &ctx.accounts.merchant_authority,
+        refund_shortfall,
+    )?;
 
     msg!(
         "Coupon {} status updated: {}",
@@ -41,3 +68,30 @@ pub fn handler(
 
     Ok(())
 }
+
+/// Move the refunds of newly cancelled coupons that aren't escrowed yet
+/// from the merchant authority into the RevenueVault
+pub(crate) fn escrow_refunds<'info>(
+    revenue_vault: Option<&mut Account<'info, RevenueVault>>,
+    merchant_authority: &Signer<'info>,
+    deposit: u64,
+) -> Result<()> {
+    if deposit == 0 {
+        return Ok(());
+    }
+    let revenue_vault = revenue_vault.ok_or(CouponError::RefundVaultRequired)?;
+    invoke(
+        &system_instruction::transfer(&merchant_authority.key(), &revenue_vault.key(), deposit),
+        &[
+            merchant_authority.to_account_info(),
+            revenue_vault.to_account_info(),
+        ],
+    )?;
+    revenue_vault.escrowed_total = revenue_vault
+        .escrowed_total
+        .checked_add(deposit)
+        .ok_or(CouponError::ArithmeticOverflow)?;
+
+    msg!("{} lamports escrowed for refunds", deposit);
+    Ok(())
+}
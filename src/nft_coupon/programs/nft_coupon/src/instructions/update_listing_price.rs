@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+
+/// Change the asking price of a resale listing
+///
+/// Pending purchases signed at the old price fail if the new price is higher
+/// (purchase_from_resale's expected_price guard).
+#[derive(Accounts)]
+pub struct UpdateListingPrice<'info> {
+    /// Resale listing PDA
+    /// Seeds: ["listing", nft_mint, seller]
+    #[account(
+        mut,
+        seeds = [b"listing", listing.mint.as_ref(), seller.key().as_ref()],
+        bump = listing.bump,
+    )]
+    pub listing: Account<'info, ResaleListing>,
+
+    pub seller: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<UpdateListingPrice>, price_lamports: u64) -> Result<()> {
+    require!(price_lamports > 0, CouponError::InvalidPrice);
+
+    let listing = &mut ctx.accounts.listing;
+    let old_price = listing.price;
+    listing.price = price_lamports;
+
+    msg!(
+        "Listing for {} repriced: {} -> {} lamports",
+        listing.mint,
+        old_price,
+        price_lamports
+    );
+
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+
+#[derive(Accounts)]
+pub struct UpdateMerchantProfile<'info> {
+    #[account(
+        mut,
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        has_one = authority @ CouponError::UnauthorizedMerchant
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(
+    ctx: Context<UpdateMerchantProfile>,
+    business_name: String,
+    logo_uri: String,
+    website: String,
+    category: CouponCategory,
+) -> Result<()> {
+    require!(
+        business_name.len() <= Merchant::MAX_NAME_LEN,
+        CouponError::BusinessNameTooLong
+    );
+    require!(
+        logo_uri.len() <= Merchant::MAX_URI_LEN && website.len() <= Merchant::MAX_URI_LEN,
+        CouponError::UriTooLong
+    );
+
+    let merchant = &mut ctx.accounts.merchant;
+    merchant.business_name = business_name;
+    merchant.logo_uri = logo_uri;
+    merchant.website = website;
+    merchant.category = category;
+
+    msg!("Merchant profile updated: {}", merchant.business_name);
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+
+#[derive(Accounts)]
+pub struct UpdatePlatformConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = admin @ CouponError::UnauthorizedAdmin
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(
+    ctx: Context<UpdatePlatformConfig>,
+    new_admin: Pubkey,
+    require_verified_merchants: bool,
+    fee_wallet: Pubkey,
+) -> Result<()> {
+    let platform_config = &mut ctx.accounts.platform_config;
+    platform_config.admin = new_admin;
+    platform_config.require_verified_merchants = require_verified_merchants;
+    platform_config.fee_wallet = fee_wallet;
+
+    msg!(
+        "Platform config updated: admin {} | require verified merchants: {} | fee wallet {}",
+        new_admin,
+        require_verified_merchants,
+        fee_wallet
+    );
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+
+/// Withdraw settled proceeds from the merchant's RevenueVault
+///
+/// CouponData accounts (writable) are passed via `remaining_accounts`.
+/// Each must belong to the merchant and be settled (redeemed, or past
+/// `settle_after`) and not cancelled pending a refund. Their escrowed price is split 97.5% to the merchant
+/// authority and 2.5% to the platform config's fee wallet, as for direct sales.
+#[derive(Accounts)]
+pub struct WithdrawRevenue<'info> {
+    #[account(
+        seeds = [b"merchant", merchant.seed_authority.as_ref()],
+        bump = merchant.bump,
+        has_one = authority @ CouponError::UnauthorizedMerchant
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    /// Merchant revenue vault
+    /// Seeds: ["revenue_vault", merchant]
+    #[account(
+        mut,
+        seeds = [b"revenue_vault", merchant.key().as_ref()],
+        bump = revenue_vault.bump,
+    )]
+    pub revenue_vault: Account<'info, RevenueVault>,
+
+    /// Platform config singleton (fee wallet)
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// Merchant authority - receives 97.5% of the settled proceeds
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Platform fee wallet - receives 2.5% of the settled proceeds
+    /// CHECK: Must be the fee wallet stored in the platform config
+    #[account(
+        mut,
+        address = platform_config.fee_wallet @ CouponError::InvalidFeeWallet,
+    )]
+    pub platform_wallet: UncheckedAccount<'info>,
+}
+
+pub fn handler(ctx: Context<WithdrawRevenue>) -> Result<()> {
+    require!(
+        !ctx.remaining_accounts.is_empty(),
+        CouponError::EmptyBatch
+    );
+
+    let merchant_key = ctx.accounts.merchant.key();
+    let current_time = Clock::get()?.unix_timestamp;
+    let mut total_released: u64 = 0;
+    let mut count: u32 = 0;
+
+    for coupon_info in ctx.remaining_accounts.iter() {
+        require_keys_eq!(*coupon_info.owner, crate::ID, CouponError::InvalidAccountType);
+        require!(coupon_info.is_writable, CouponError::InvalidAccountType);
+
+        let mut data = coupon_info.try_borrow_mut_data()?;
+        let mut coupon_data = CouponData::try_deserialize(&mut &data[..])?;
+        require_keys_eq!(
+            coupon_data.merchant,
+            merchant_key,
+            CouponError::UnauthorizedMerchant
+        );
+
+        // Skip coupons that are already settled, still held back, or owed to the buyer
+        if coupon_data.escrowed_lamports == 0
+            || coupon_data.settle_after > current_time
+            || coupon_data.is_refundable()
+        {
+            continue;
+        }
+
+        total_released = total_released
+            .checked_add(coupon_data.escrowed_lamports)
+            .ok_or(CouponError::ArithmeticOverflow)?;
+        count += 1;
+
+        coupon_data.escrowed_lamports = 0;
+        coupon_data.try_serialize(&mut &mut data[..])?;
+    }
+
+    require!(total_released > 0, CouponError::NothingToWithdraw);
+
+    // Same split as a direct primary sale
+    let platform_fee = (total_released
+        .checked_mul(25)
+        .ok_or(CouponError::ArithmeticOverflow)?)
+        .checked_div(1000)
+        .ok_or(CouponError::ArithmeticOverflow)?; // 2.5%
+    let merchant_amount = total_released
+        .checked_sub(platform_fee)
+        .ok_or(CouponError::ArithmeticOverflow)?; // 97.5%
+
+    let revenue_vault = &mut ctx.accounts.revenue_vault;
+    revenue_vault.escrowed_total = revenue_vault
+        .escrowed_total
+        .checked_sub(total_released)
+        .ok_or(CouponError::ArithmeticOverflow)?;
+
+    // Vault is program-owned: move lamports directly
+    revenue_vault.sub_lamports(total_released)?;
+    ctx.accounts.authority.add_lamports(merchant_amount)?;
+    if platform_fee > 0 {
+        ctx.accounts.platform_wallet.add_lamports(platform_fee)?;
+    }
+
+    msg!(
+        "Revenue withdrawn: {} coupons | Total {} lamports | Merchant {} | Platform {}",
+        count,
+        total_released,
+        merchant_amount,
+        platform_fee
+    );
+
+    emit!(RevenueWithdrawnEvent {
+        merchant: merchant_key,
+        coupons: count,
+        total: total_released,
+        merchant_amount,
+        platform_fee,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct RevenueWithdrawnEvent {
+    pub merchant: Pubkey,
+    pub coupons: u32,
+    pub total: u64,
+    pub merchant_amount: u64,
+    pub platform_fee: u64,
+    pub timestamp: i64,
+}
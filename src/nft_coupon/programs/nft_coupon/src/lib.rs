@@ -2,8 +2,12 @@ use anchor_lang::prelude::*;
 
 declare_id!("RECcAGSNVfAdGeTsR92jMUM2DBuedSqpAn9W8pNrLi7");
 
+pub mod bundle;
 pub mod errors;
+pub mod gate;
 pub mod instructions;
+pub mod merkle;
+pub mod migration;
 pub mod state;
 
 use instructions::*;
@@ -25,35 +29,44 @@ pub mod nft_coupon {
     /// Create a new NFT coupon
     /// Mints an NFT with Metaplex metadata and creates coupon data
     /// NFT is minted to Escrow PDA (program-controlled)
+    /// Full title, description and terms hash are stored on-chain in CouponData
+    /// Unverified merchants are blocked or flagged depending on platform config
     pub fn create_coupon(
         ctx: Context<CreateCoupon>,
         title: String,
         description: String,
-        discount_percentage: u8,
+        discount_kind: DiscountKind,
         expiry_date: i64,
         category: CouponCategory,
         max_redemptions: u8,
         metadata_uri: String,
         price: u64, // NEW: Price in lamports (0 = free, >0 = paid)
+        terms_hash: [u8; 32],
     ) -> Result<()> {
         instructions::create_coupon::handler(
             ctx,
             title,
             description,
-            discount_percentage,
+            discount_kind,
             expiry_date,
             category,
             max_redemptions,
             metadata_uri,
             price,
+            terms_hash,
         )
     }
 
     /// Claim a free coupon (price = 0)
     /// Transfers NFT from Escrow PDA to user
     /// Magic Eden style: Program-controlled transfer, no backend signature
-    pub fn claim_coupon(ctx: Context<ClaimCoupon>) -> Result<()> {
-        instructions::claim_coupon::handler(ctx)
+    /// Allowlisted coupons require a Merkle proof of the caller's wallet
+    /// Token-gated coupons require the gate accounts in remaining_accounts
+    pub fn claim_coupon(
+        ctx: Context<ClaimCoupon>,
+        proof: Option<AllowlistProof>,
+    ) -> Result<()> {
+        instructions::claim_coupon::handler(ctx, proof)
     }
 
     /// Purchase a paid coupon (price > 0)
@@ -61,8 +74,15 @@ pub mod nft_coupon {
     /// - User pays SOL (97.5% merchant, 2.5% platform)
     /// - NFT transferred from Escrow PDA to buyer
     /// - All or nothing (transaction fails if any step fails)
-    pub fn purchase_coupon(ctx: Context<PurchaseCoupon>) -> Result<()> {
-        instructions::purchase_coupon::handler(ctx)
+    /// - Allowlisted coupons require a Merkle proof of the buyer's wallet
+    /// - Token-gated coupons require the gate accounts in remaining_accounts
+    /// - Fails if the current price (fixed or price curve) exceeds max_price
+    pub fn purchase_coupon(
+        ctx: Context<PurchaseCoupon>,
+        proof: Option<AllowlistProof>,
+        max_price: u64,
+    ) -> Result<()> {
+        instructions::purchase_coupon::handler(ctx, proof, max_price)
     }
 
     /// Redeem a coupon
@@ -71,8 +91,16 @@ pub mod nft_coupon {
         instructions::redeem_coupon::handler(ctx)
     }
 
+    /// Redeem part of a stored-value (gift card) coupon
+    /// Deducts amount from the balance, burns the NFT when it reaches zero
+    pub fn redeem_amount(ctx: Context<RedeemAmount>, amount: u64) -> Result<()> {
+        instructions::redeem_amount::handler(ctx, amount)
+    }
+
     /// Update coupon active status
     /// Allows merchant to deactivate/reactivate a coupon
+    /// Deactivating a sold, unredeemed coupon cancels it permanently and escrows the refund
+    /// (deposited by the merchant for direct-mode sales)
     pub fn update_coupon_status(
         ctx: Context<UpdateCouponStatus>,
         is_active: bool,
@@ -80,6 +108,16 @@ pub mod nft_coupon {
         instructions::update_coupon_status::handler(ctx, is_active)
     }
 
+    /// Update active status of many coupons at once
+    /// CouponData accounts are passed via remaining_accounts
+    /// Lets merchants pause/resume a whole promotion in one transaction
+    pub fn batch_update_coupon_status(
+        ctx: Context<BatchUpdateCouponStatus>,
+        is_active: bool,
+    ) -> Result<()> {
+        instructions::batch_update_coupon_status::handler(ctx, is_active)
+    }
+
     /// Transfer NFT coupon between users (P2P resale)
     /// Atomic transaction: SOL payment + NFT transfer
     /// - Buyer pays SOL (97.5% to seller, 2.5% to platform)
@@ -97,8 +135,27 @@ pub mod nft_coupon {
     /// Transfers NFT from seller's wallet to Resale Escrow PDA
     /// Seller signs this transaction
     /// Industry-standard approach used by Magic Eden, OpenSea, Tensor
-    pub fn list_for_resale(ctx: Context<ListForResale>) -> Result<()> {
-        instructions::list_for_resale::handler(ctx)
+    /// Asking price and expiry (None = coupon expiry) stored in the ResaleListing PDA
+    pub fn list_for_resale(
+        ctx: Context<ListForResale>,
+        price_lamports: u64,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        instructions::list_for_resale::handler(ctx, price_lamports, expires_at)
+    }
+
+    /// Change the asking price of a resale listing (seller only)
+    pub fn update_listing_price(
+        ctx: Context<UpdateListingPrice>,
+        price_lamports: u64,
+    ) -> Result<()> {
+        instructions::update_listing_price::handler(ctx, price_lamports)
+    }
+
+    /// Return an expired listing's NFT to the seller and close it (permissionless)
+    /// Cranker earns a small bounty from the listing rent
+    pub fn crank_expired_listing(ctx: Context<CrankExpiredListing>) -> Result<()> {
+        instructions::crank_expired_listing::handler(ctx)
     }
 
     /// Purchase NFT coupon from resale marketplace (Step 2 of escrow-based resale)
@@ -107,10 +164,314 @@ pub mod nft_coupon {
     /// - NFT transferred from Resale Escrow PDA to buyer
     /// - Seller does NOT need to sign (NFT already in escrow)
     /// - All or nothing (transaction fails if any step fails)
+    /// - Fails if the listing price exceeds expected_price
     pub fn purchase_from_resale(
         ctx: Context<PurchaseFromResale>,
+        expected_price: u64,
+    ) -> Result<()> {
+        instructions::purchase_from_resale::handler(ctx, expected_price)
+    }
+
+    /// Set or clear a coupon's Merkle allowlist
+    /// Allows merchant to restrict claims/purchases to VIP wallets ([0; 32] = open)
+    pub fn set_coupon_allowlist(
+        ctx: Context<SetCouponAllowlist>,
+        allowlist_root: [u8; 32],
+    ) -> Result<()> {
+        instructions::set_coupon_allowlist::handler(ctx, allowlist_root)
+    }
+
+    /// Set or clear a coupon's token gate
+    /// Restricts claims/purchases to holders of a token balance or verified collection NFT
+    /// Locked once the NFT has left escrow
+    pub fn set_coupon_gate(
+        ctx: Context<SetCouponGate>,
+        gate_kind: GateKind,
+        gate_mint: Pubkey,
+        gate_min_amount: u64,
+    ) -> Result<()> {
+        instructions::set_coupon_gate::handler(ctx, gate_kind, gate_mint, gate_min_amount)
+    }
+
+    /// Set a coupon's sale and redemption windows (0 = unbounded on that side)
+    /// Lets merchants pre-mint scheduled drops, e.g. Black Friday
+    /// Call in the same transaction as create_coupon to keep the coupon off sale
+    pub fn set_coupon_windows(
+        ctx: Context<SetCouponWindows>,
+        sale_start: i64,
+        sale_end: i64,
+        redeem_start: i64,
+        redeem_end: i64,
+    ) -> Result<()> {
+        instructions::set_coupon_windows::handler(
+            ctx,
+            sale_start,
+            sale_end,
+            redeem_start,
+            redeem_end,
+        )
+    }
+
+    /// Set or clear a coupon's recurring redemption hours
+    /// e.g. happy hour coupons valid only Mon-Fri 15:00-18:00 local time
+    pub fn set_redemption_schedule(
+        ctx: Context<SetRedemptionSchedule>,
+        schedule: Option<RedemptionSchedule>,
+    ) -> Result<()> {
+        instructions::set_redemption_schedule::handler(ctx, schedule)
+    }
+
+    /// Set the minimum time between redemptions of a multi-use coupon
+    /// Stops "5 coffees" coupons being burned through back-to-back (0 = no cooldown)
+    pub fn set_redemption_cooldown(
+        ctx: Context<SetRedemptionCooldown>,
+        min_seconds_between_redemptions: i64,
+    ) -> Result<()> {
+        instructions::set_redemption_cooldown::handler(ctx, min_seconds_between_redemptions)
+    }
+
+    /// Migrate a coupon account to the current layout version
+    /// Reallocs and zero-fills old accounts so live coupons survive upgrades
+    pub fn migrate_coupon(ctx: Context<MigrateCoupon>) -> Result<()> {
+        instructions::migrate_coupon::handler(ctx)
+    }
+
+    /// Migrate a merchant account to the current layout version
+    /// Reallocs and zero-fills old accounts so live merchants survive upgrades
+    pub fn migrate_merchant(ctx: Context<MigrateMerchant>) -> Result<()> {
+        instructions::migrate_merchant::handler(ctx)
+    }
+
+    /// Update merchant profile (name, logo, website, category)
+    pub fn update_merchant_profile(
+        ctx: Context<UpdateMerchantProfile>,
+        business_name: String,
+        logo_uri: String,
+        website: String,
+        category: CouponCategory,
+    ) -> Result<()> {
+        instructions::update_merchant_profile::handler(
+            ctx,
+            business_name,
+            logo_uri,
+            website,
+            category,
+        )
+    }
+
+    /// Propose a new merchant authority (Step 1 of authority rotation)
+    /// Pass None to cancel a pending proposal
+    pub fn propose_authority(
+        ctx: Context<ProposeAuthority>,
+        new_authority: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::propose_authority::handler(ctx, new_authority)
+    }
+
+    /// Accept a pending merchant authority (Step 2 of authority rotation)
+    /// New wallet takes control; merchant PDA address is unchanged
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        instructions::accept_authority::handler(ctx)
+    }
+
+    /// Initialize the platform config (program upgrade authority only)
+    /// Upgrade authority becomes the platform admin
+    pub fn initialize_platform(
+        ctx: Context<InitializePlatform>,
+        require_verified_merchants: bool,
+        fee_wallet: Pubkey,
+    ) -> Result<()> {
+        instructions::initialize_platform::handler(ctx, require_verified_merchants, fee_wallet)
+    }
+
+    /// Update platform admin, verification policy and fee wallet (admin only)
+    pub fn update_platform_config(
+        ctx: Context<UpdatePlatformConfig>,
+        new_admin: Pubkey,
+        require_verified_merchants: bool,
+        fee_wallet: Pubkey,
+    ) -> Result<()> {
+        instructions::update_platform_config::handler(
+            ctx,
+            new_admin,
+            require_verified_merchants,
+            fee_wallet,
+        )
+    }
+
+    /// Mark a merchant as verified (admin only)
+    pub fn verify_merchant(ctx: Context<VerifyMerchant>) -> Result<()> {
+        instructions::verify_merchant::handler(ctx)
+    }
+
+    /// Revoke a merchant's verification (admin only)
+    pub fn revoke_merchant(ctx: Context<RevokeMerchant>) -> Result<()> {
+        instructions::revoke_merchant::handler(ctx)
+    }
+
+    /// Suspend or reinstate a merchant (admin only)
+    /// Freezes claims, purchases, redemptions and resales of all its coupons at once
+    pub fn set_merchant_suspension(
+        ctx: Context<SetMerchantSuspension>,
+        is_suspended: bool,
+    ) -> Result<()> {
+        instructions::set_merchant_suspension::handler(ctx, is_suspended)
+    }
+
+    /// Close a coupon that can no longer be used (unsold, expired or fully redeemed)
+    /// Burns an unsold NFT, closes the escrow and coupon data, returns rent to merchant
+    pub fn close_coupon(ctx: Context<CloseCoupon>) -> Result<()> {
+        instructions::close_coupon::handler(ctx)
+    }
+
+    /// Close a merchant account once all its coupons are closed
+    /// Also closes its RevenueVault (which must hold no escrowed proceeds)
+    /// Returns rent and leftover vault lamports to the merchant authority
+    pub fn close_merchant(ctx: Context<CloseMerchant>) -> Result<()> {
+        instructions::close_merchant::handler(ctx)
+    }
+
+    /// Configure deferred settlement of primary sale proceeds
+    /// When enabled, purchases pay into the merchant's RevenueVault
+    pub fn configure_settlement(
+        ctx: Context<ConfigureSettlement>,
+        deferred_settlement: bool,
+        holdback_seconds: i64,
+    ) -> Result<()> {
+        instructions::configure_settlement::handler(ctx, deferred_settlement, holdback_seconds)
+    }
+
+    /// Withdraw settled proceeds (redeemed or past holdback) from the RevenueVault
+    /// CouponData accounts passed via remaining_accounts
+    pub fn withdraw_revenue(ctx: Context<WithdrawRevenue>) -> Result<()> {
+        instructions::withdraw_revenue::handler(ctx)
+    }
+
+    /// Refund a cancelled, unused paid coupon from the merchant's RevenueVault
+    /// Burns the NFT and returns the escrowed purchase price to its current holder
+    pub fn refund_coupon(ctx: Context<RefundCoupon>) -> Result<()> {
+        instructions::refund_coupon::handler(ctx)
+    }
+
+    /// Place an offer on a coupon NFT (SOL locked in the Offer PDA)
+    pub fn place_offer(ctx: Context<PlaceOffer>, amount: u64, expires_at: i64) -> Result<()> {
+        instructions::place_offer::handler(ctx, amount, expires_at)
+    }
+
+    /// Cancel an offer and reclaim the locked SOL
+    pub fn cancel_offer(ctx: Context<CancelOffer>) -> Result<()> {
+        instructions::cancel_offer::handler(ctx)
+    }
+
+    /// Accept an offer on a held coupon NFT
+    /// NFT to bidder, offer split 97.5% seller / 2.5% platform
+    pub fn accept_offer(ctx: Context<AcceptOffer>) -> Result<()> {
+        instructions::accept_offer::handler(ctx)
+    }
+
+    /// Place a floor bid on any coupon from a merchant
+    /// Optional category and minimum discount filters
+    pub fn place_collection_offer(
+        ctx: Context<PlaceCollectionOffer>,
+        offer_id: u64,
+        amount: u64,
+        expires_at: i64,
+        category: Option<CouponCategory>,
+        min_discount_percentage: u8,
+    ) -> Result<()> {
+        instructions::place_collection_offer::handler(
+            ctx,
+            offer_id,
+            amount,
+            expires_at,
+            category,
+            min_discount_percentage,
+        )
+    }
+
+    /// Cancel a collection offer and reclaim the locked SOL
+    pub fn cancel_collection_offer(ctx: Context<CancelCollectionOffer>) -> Result<()> {
+        instructions::cancel_collection_offer::handler(ctx)
+    }
+
+    /// Fill a collection offer with a matching coupon
+    /// Merchant and terms checked against CouponData at acceptance
+    pub fn accept_collection_offer(ctx: Context<AcceptCollectionOffer>) -> Result<()> {
+        instructions::accept_collection_offer::handler(ctx)
+    }
+
+    /// Auction a held coupon NFT (resale)
+    /// NFT held in the Auction Escrow PDA until settle_auction
+    pub fn create_auction(
+        ctx: Context<CreateAuction>,
+        reserve_price: u64,
+        end_time: i64,
+        min_increment: u64,
+    ) -> Result<()> {
+        instructions::create_auction::handler(ctx, reserve_price, end_time, min_increment)
+    }
+
+    /// Auction an unsold coupon NFT out of the merchant's Escrow PDA (primary sale)
+    /// Needs an open sale window; coupons with an allowlist or token gate are rejected
+    pub fn create_primary_auction(
+        ctx: Context<CreatePrimaryAuction>,
+        reserve_price: u64,
+        end_time: i64,
+        min_increment: u64,
+    ) -> Result<()> {
+        instructions::create_primary_auction::handler(ctx, reserve_price, end_time, min_increment)
+    }
+
+    /// Bid on an auction - refunds the previous top bidder
+    /// Late bids extend the auction (anti-sniping)
+    pub fn place_bid(ctx: Context<PlaceBid>, amount: u64) -> Result<()> {
+        instructions::place_bid::handler(ctx, amount)
+    }
+
+    /// Settle an ended auction (permissionless)
+    /// NFT to winner, proceeds split 97.5% seller / 2.5% platform
+    pub fn settle_auction(ctx: Context<SettleAuction>) -> Result<()> {
+        instructions::settle_auction::handler(ctx)
+    }
+
+    /// Set or clear a Dutch-auction price curve on a paid coupon
+    /// purchase_coupon then charges the curve's current price
+    pub fn set_price_curve(
+        ctx: Context<SetPriceCurve>,
+        price_curve: Option<PriceCurve>,
+    ) -> Result<()> {
+        instructions::set_price_curve::handler(ctx, price_curve)
+    }
+
+    /// List 2-8 coupon NFTs as one bundle for one price
+    /// Per-coupon accounts passed via remaining_accounts
+    pub fn list_bundle<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ListBundle<'info>>,
+        bundle_id: u64,
         price_lamports: u64,
     ) -> Result<()> {
-        instructions::purchase_from_resale::handler(ctx, price_lamports)
+        instructions::list_bundle::handler(ctx, bundle_id, price_lamports)
+    }
+
+    /// Purchase a whole bundle atomically (platform fee computed once)
+    /// Fails if the bundle price exceeds expected_price
+    pub fn purchase_bundle<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PurchaseBundle<'info>>,
+        expected_price: u64,
+    ) -> Result<()> {
+        instructions::purchase_bundle::handler(ctx, expected_price)
+    }
+
+    /// Delist a bundle and return its NFTs to the seller
+    pub fn cancel_bundle<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelBundle<'info>>,
+    ) -> Result<()> {
+        instructions::cancel_bundle::handler(ctx)
+    }
+
+    /// Delist a resale coupon and return the NFT to the seller (seller only)
+    /// Works with or without a ResaleListing, and before the listing expires
+    pub fn cancel_listing(ctx: Context<CancelListing>) -> Result<()> {
+        instructions::cancel_listing::handler(ctx)
     }
 }
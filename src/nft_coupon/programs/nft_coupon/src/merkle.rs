@@ -0,0 +1,126 @@
+use anchor_lang::prelude::*;
+use solana_sha256_hasher::hashv;
+use crate::state::*;
+use crate::errors::*;
+
+/// Build the allowlist leaf for a wallet: sha256(wallet || quota as u16 LE)
+pub fn allowlist_leaf(wallet: &Pubkey, quota: u16) -> [u8; 32] {
+    hashv(&[wallet.as_ref(), &quota.to_le_bytes()]).to_bytes()
+}
+
+/// Verify a Merkle proof using sorted-pair hashing
+/// Sorting each pair means proofs don't need left/right position flags
+pub fn verify_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            hashv(&[&node, sibling]).to_bytes()
+        } else {
+            hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+    computed == *root
+}
+
+/// Enforce the coupon's allowlist (if any) for `wallet`
+///
+/// - Open coupons (zero root) pass without a proof
+/// - Allowlisted coupons require a valid proof of (wallet, quota)
+/// - Quota-limited leaves (quota > 0) require the receipt PDA, which is incremented
+pub fn require_allowlisted(
+    coupon_data: &CouponData,
+    wallet: &Pubkey,
+    proof: Option<&AllowlistProof>,
+    receipt: Option<&mut AllowlistReceipt>,
+) -> Result<()> {
+    if !coupon_data.has_allowlist() {
+        return Ok(());
+    }
+
+    let proof = proof.ok_or(CouponError::NotOnAllowlist)?;
+    require!(
+        verify_proof(
+            &proof.proof,
+            &coupon_data.allowlist_root,
+            allowlist_leaf(wallet, proof.quota),
+        ),
+        CouponError::NotOnAllowlist
+    );
+
+    if proof.quota > 0 {
+        let receipt = receipt.ok_or(CouponError::MissingAllowlistReceipt)?;
+        require!(
+            receipt.count < proof.quota,
+            CouponError::AllowlistQuotaExceeded
+        );
+        receipt.wallet = *wallet;
+        receipt.count = receipt
+            .count
+            .checked_add(1)
+            .ok_or(CouponError::ArithmeticOverflow)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_pair(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        if a <= b {
+            hashv(&[&a, &b]).to_bytes()
+        } else {
+            hashv(&[&b, &a]).to_bytes()
+        }
+    }
+
+    /// Four-leaf tree: returns (leaves, root)
+    fn tree() -> ([[u8; 32]; 4], [u8; 32]) {
+        let leaves = [
+            allowlist_leaf(&Pubkey::new_from_array([1; 32]), 0),
+            allowlist_leaf(&Pubkey::new_from_array([2; 32]), 1),
+            allowlist_leaf(&Pubkey::new_from_array([3; 32]), 2),
+            allowlist_leaf(&Pubkey::new_from_array([4; 32]), 0),
+        ];
+        let root = hash_pair(
+            hash_pair(leaves[0], leaves[1]),
+            hash_pair(leaves[2], leaves[3]),
+        );
+        (leaves, root)
+    }
+
+    #[test]
+    fn valid_proofs_verify() {
+        let (leaves, root) = tree();
+        let left = hash_pair(leaves[0], leaves[1]);
+        let right = hash_pair(leaves[2], leaves[3]);
+        assert!(verify_proof(&[leaves[1], right], &root, leaves[0]));
+        assert!(verify_proof(&[leaves[0], right], &root, leaves[1]));
+        assert!(verify_proof(&[leaves[3], left], &root, leaves[2]));
+        assert!(verify_proof(&[leaves[2], left], &root, leaves[3]));
+    }
+
+    #[test]
+    fn wrong_leaf_is_rejected() {
+        let (leaves, root) = tree();
+        let right = hash_pair(leaves[2], leaves[3]);
+        // Same wallet with a different quota is a different leaf
+        let forged = allowlist_leaf(&Pubkey::new_from_array([1; 32]), 5);
+        assert!(!verify_proof(&[leaves[1], right], &root, forged));
+    }
+
+    #[test]
+    fn tampered_sibling_is_rejected() {
+        let (leaves, root) = tree();
+        let mut right = hash_pair(leaves[2], leaves[3]);
+        right[0] ^= 1;
+        assert!(!verify_proof(&[leaves[1], right], &root, leaves[0]));
+    }
+
+    #[test]
+    fn empty_proof_matches_single_leaf_root() {
+        let (leaves, root) = tree();
+        assert!(verify_proof(&[], &leaves[0], leaves[0]));
+        assert!(!verify_proof(&[], &root, leaves[0]));
+    }
+}
@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use crate::errors::*;
+
+/// Read a program account into memory, zero-extended to `max_len`
+///
+/// Fields added since the account was created decode as their zero value
+/// (0, false, None, empty string or the first enum variant).
+pub fn load_zero_extended<T: AccountDeserialize + Discriminator>(
+    info: &AccountInfo,
+    max_len: usize,
+) -> Result<T> {
+    require_keys_eq!(*info.owner, crate::ID, CouponError::InvalidAccountType);
+
+    let mut buf = info.try_borrow_data()?.to_vec();
+    require!(
+        buf.starts_with(T::DISCRIMINATOR),
+        CouponError::InvalidAccountType
+    );
+    if buf.len() < max_len {
+        buf.resize(max_len, 0);
+    }
+
+    T::try_deserialize(&mut &buf[..])
+}
+
+/// Grow `info` to `new_len`, topping up rent from `payer`, then write `data`
+pub fn resize_and_store<'info, T: AccountSerialize>(
+    info: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+    data: &T,
+) -> Result<()> {
+    if new_len > info.data_len() {
+        let required = Rent::get()?.minimum_balance(new_len);
+        let shortfall = required.saturating_sub(info.lamports());
+        if shortfall > 0 {
+            transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    Transfer {
+                        from: payer.clone(),
+                        to: info.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        info.resize(new_len)?;
+    }
+
+    let mut account_data = info.try_borrow_mut_data()?;
+    data.try_serialize(&mut &mut account_data[..])
+}
@@ -1,6 +1,14 @@
 use anchor_lang::prelude::*;
+use crate::errors::*;
 
 /// Merchant account - PDA to track merchant info and coupon creation
+///
+/// Layout evolution: new fields always go just before `reserved`.
+/// - Fields that fit and start at zero are carved out of `reserved`; older accounts
+///   already hold zeros there, so no migration is needed.
+/// - Otherwise (too big, or needing a computed initial value) the account grows by
+///   the fields' size and `CURRENT_VERSION` is bumped, so that `migrate_merchant` can
+///   zero-extend older accounts and fill them in. `reserved` keeps its size as slack.
 #[account]
 pub struct Merchant {
     /// Merchant's wallet address (authority)
@@ -11,18 +19,170 @@ pub struct Merchant {
     pub total_coupons_created: u64,
     /// Bump seed for PDA
     pub bump: u8,
+    /// Account layout version (0 = legacy, pre-versioning)
+    pub version: u8,
+    /// Authority key the PDA was derived from (stays fixed across authority rotations)
+    /// Seeds: ["merchant", seed_authority]
+    pub seed_authority: Pubkey,
+    /// Logo image URI
+    pub logo_uri: String,
+    /// Business website
+    pub website: String,
+    /// Primary business category
+    pub category: CouponCategory,
+    /// Authority proposed via propose_authority, awaiting accept_authority
+    pub pending_authority: Option<Pubkey>,
+    /// Platform verification status (set by platform admin)
+    pub verification_status: VerificationStatus,
+    /// Admin who last verified or revoked this merchant
+    pub verifier: Pubkey,
+    /// Suspended by platform admin - freezes claims, purchases, redemptions and resales
+    pub is_suspended: bool,
+    /// Coupons created and not yet closed (must be 0 to close the merchant)
+    pub open_coupons: u64,
+    /// Free coupons claimed (claim_coupon)
+    pub total_claims: u64,
+    /// Paid coupons sold (purchase_coupon)
+    pub total_sales: u64,
+    /// Gross primary sale revenue in lamports, before platform fees
+    pub gross_revenue_lamports: u64,
+    /// Platform fees paid on primary sales, in lamports
+    pub fees_paid: u64,
+    /// Redemptions across all coupons (redeem_coupon + redeem_amount)
+    pub total_redemptions: u64,
+    /// Primary sale proceeds are held in the RevenueVault until redemption or holdback expiry
+    pub deferred_settlement: bool,
+    /// Seconds after purchase before unredeemed proceeds can be withdrawn (deferred mode)
+    pub holdback_seconds: i64,
+    /// Distinct wallets that redeemed at least once (tracked via RedeemerRecord)
+    pub unique_redeemers: u64,
+    /// Reserved for future fields (must stay zeroed)
+    pub reserved: [u8; 13],
 }
 
 impl Merchant {
     pub const MAX_NAME_LEN: usize = 100;
+    pub const MAX_URI_LEN: usize = 200;
+    pub const CURRENT_VERSION: u8 = 4;
 
     /// Calculate space needed for Merchant account
     /// 8 (discriminator) + 32 (pubkey) + 4 + MAX_NAME_LEN (string) + 8 (u64) + 1 (u8)
-    pub const LEN: usize = 8 + 32 + 4 + Self::MAX_NAME_LEN + 8 + 1;
+    /// + 1 (version) + 32 (seed_authority) + 4 + MAX_URI_LEN (logo_uri)
+    /// + 4 + MAX_URI_LEN (website) + 1 (category enum) + 33 (option pubkey)
+    /// + 1 (verification enum) + 32 (verifier) + 1 (bool) + 8 (open_coupons)
+    /// + 5 * 8 (stats counters) + 1 (deferred_settlement) + 8 (holdback_seconds)
+    /// + 8 (unique_redeemers) + 13 (reserved)
+    pub const LEN: usize = 8 + 32 + 4 + Self::MAX_NAME_LEN + 8 + 1
+        + 1 + 32 + 4 + Self::MAX_URI_LEN
+        + 4 + Self::MAX_URI_LEN + 1 + 33
+        + 1 + 32 + 1 + 8
+        + 5 * 8 + 1 + 8
+        + 8 + 13;
+
+    pub fn is_verified(&self) -> bool {
+        self.verification_status == VerificationStatus::Verified
+    }
+
+    /// Count a free claim
+    pub fn record_claim(&mut self) -> Result<()> {
+        self.total_claims = self
+            .total_claims
+            .checked_add(1)
+            .ok_or(CouponError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Count a primary sale and its revenue split
+    pub fn record_sale(&mut self, price: u64, platform_fee: u64) -> Result<()> {
+        self.total_sales = self
+            .total_sales
+            .checked_add(1)
+            .ok_or(CouponError::ArithmeticOverflow)?;
+        self.gross_revenue_lamports = self
+            .gross_revenue_lamports
+            .checked_add(price)
+            .ok_or(CouponError::ArithmeticOverflow)?;
+        self.fees_paid = self
+            .fees_paid
+            .checked_add(platform_fee)
+            .ok_or(CouponError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Count a redemption (`first_for_wallet` = the redeemer's first at this merchant)
+    pub fn record_redemption(&mut self, first_for_wallet: bool) -> Result<()> {
+        self.total_redemptions = self
+            .total_redemptions
+            .checked_add(1)
+            .ok_or(CouponError::ArithmeticOverflow)?;
+        if first_for_wallet {
+            self.unique_redeemers = self
+                .unique_redeemers
+                .checked_add(1)
+                .ok_or(CouponError::ArithmeticOverflow)?;
+        }
+        Ok(())
+    }
+}
+
+/// Merchant verification status, managed by the platform admin
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VerificationStatus {
+    #[default]
+    Unverified,
+    Verified,
+    Revoked,
+}
+
+/// Per-merchant vault holding deferred primary sale proceeds
+/// Seeds: ["revenue_vault", merchant]
+///
+/// Lamports above rent are owed either to the merchant (after redemption or
+/// holdback, via withdraw_revenue) or back to buyers (refunds).
+#[account]
+pub struct RevenueVault {
+    /// Merchant this vault belongs to
+    pub merchant: Pubkey,
+    /// Lamports currently escrowed across all of the merchant's coupons
+    pub escrowed_total: u64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl RevenueVault {
+    /// 8 (discriminator) + 32 (merchant) + 8 (u64) + 1 (bump)
+    pub const LEN: usize = 8 + 32 + 8 + 1;
+}
+
+/// Platform configuration - singleton PDA controlled by the platform admin
+/// Seeds: ["platform_config"]
+#[account]
+pub struct PlatformConfig {
+    /// Platform admin (verifies/revokes merchants)
+    pub admin: Pubkey,
+    /// true = unverified merchants cannot create coupons
+    /// false = allowed, but their NFTs are flagged with the UNVERIFIED symbol
+    pub require_verified_merchants: bool,
+    /// Bump seed for PDA
+    pub bump: u8,
+    /// Wallet receiving platform fees on settled RevenueVault withdrawals
+    pub fee_wallet: Pubkey,
+    /// Reserved for future fields (must stay zeroed)
+    pub reserved: [u8; 32],
+}
+
+impl PlatformConfig {
+    /// Metaplex symbol applied to coupons from unverified merchants
+    pub const UNVERIFIED_SYMBOL: &'static str = "UNVERIFIED";
+
+    /// 8 (discriminator) + 32 (admin) + 1 (bool) + 1 (bump) + 32 (fee_wallet) + 32 (reserved)
+    pub const LEN: usize = 8 + 32 + 1 + 1 + 32 + 32;
 }
 
 /// Coupon metadata structure
 /// This data is stored on-chain and linked to the NFT
+///
+/// Layout evolution follows the same rules as `Merchant` (see `migrate_coupon`)
 #[account]
 pub struct CouponData {
     /// Mint address of the NFT coupon
@@ -30,6 +190,7 @@ pub struct CouponData {
     /// Merchant who created this coupon
     pub merchant: Pubkey,
     /// Discount percentage (0-100)
+    /// Mirrors the percentage of Percentage/PercentageCapped discounts, 0 for other kinds
     pub discount_percentage: u8,
     /// Expiry date (Unix timestamp)
     pub expiry_date: i64,
@@ -43,15 +204,625 @@ pub struct CouponData {
     pub is_active: bool,
     /// Price in lamports (0 = free coupon, >0 = paid coupon)
     /// 1 SOL = 1,000,000,000 lamports
+    /// Superseded by `price_curve` for Dutch auction sales
     pub price: u64,
     /// Bump seed for PDA
     pub bump: u8,
+    /// Merkle root of allowed claimers/buyers ([0; 32] = open to everyone)
+    /// Leaves are sha256(wallet_pubkey || quota as u16 LE)
+    pub allowlist_root: [u8; 32],
+    /// Token gate type (None = no holding requirement)
+    pub gate_kind: GateKind,
+    /// Required token mint (TokenBalance) or verified collection mint (Collection)
+    pub gate_mint: Pubkey,
+    /// Minimum amount the caller's token account must hold
+    pub gate_min_amount: u64,
+    /// Sale window start - claim/purchase not allowed before (0 = on sale at creation)
+    pub sale_start: i64,
+    /// Sale window end - claim/purchase not allowed from (0 = until expiry_date)
+    pub sale_end: i64,
+    /// Redemption window start - redeem not allowed before (0 = redeemable at creation)
+    pub redeem_start: i64,
+    /// Redemption window end - redeem not allowed from (0 = until expiry_date)
+    pub redeem_end: i64,
+    /// Recurring redemption hours, e.g. happy hour Mon-Fri 15:00-18:00 (None = any time)
+    pub redemption_schedule: Option<RedemptionSchedule>,
+    /// Minimum seconds between two redemptions of a multi-use coupon (0 = no cooldown)
+    pub min_seconds_between_redemptions: i64,
+    /// Timestamp of the last redemption (0 = never redeemed)
+    pub last_redeemed_at: i64,
+    /// What the POS should apply on redemption
+    pub discount_kind: DiscountKind,
+    /// Remaining stored value for StoredValue coupons, in smallest currency units
+    pub balance: u64,
+    /// Full coupon title (Metaplex metadata only holds the first 32 chars)
+    pub title: String,
+    /// Coupon description / deal details
+    pub description: String,
+    /// SHA-256 hash of the legal terms and fine print
+    /// Lets holders verify terms without trusting the metadata URI host
+    pub terms_hash: [u8; 32],
+    /// Account layout version (0 = legacy, pre-versioning)
+    pub version: u8,
+    /// Purchase price held in the merchant's RevenueVault (0 = settled or not deferred)
+    pub escrowed_lamports: u64,
+    /// Escrowed proceeds become withdrawable from this time (moved up on redemption)
+    pub settle_after: i64,
+    /// Wallet that bought the coupon from the merchant
+    pub purchaser: Pubkey,
+    /// Declining price for Dutch-auction primary sales (None = fixed `price`)
+    pub price_curve: Option<PriceCurve>,
+    /// Cancelled after being paid for and before being honored - the escrow belongs
+    /// to the holder and the coupon can never be reactivated
+    pub refund_owed: bool,
+    /// Price the purchaser paid on the primary sale, in either settlement mode (0 = not sold)
+    pub paid_lamports: u64,
+    /// Reserved for future fields (must stay zeroed)
+    pub reserved: [u8; 7],
 }
 
 impl CouponData {
     /// Calculate space needed for CouponData account
     /// 8 (discriminator) + 32 (mint) + 32 (merchant) + 1 (u8) + 8 (i64) + 1 (enum) + 1 (u8) + 1 (u8) + 1 (bool) + 8 (price) + 1 (bump)
-    pub const LEN: usize = 8 + 32 + 32 + 1 + 8 + 1 + 1 + 1 + 1 + 8 + 1;
+    /// + 32 (allowlist_root) + 1 (gate enum) + 32 (gate_mint) + 8 (gate_min_amount)
+    /// + 8 (sale_start) + 8 (sale_end) + 8 (redeem_start) + 8 (redeem_end)
+    /// + 1 + RedemptionSchedule::LEN (option)
+    /// + 8 (min_seconds_between_redemptions) + 8 (last_redeemed_at)
+    /// + DiscountKind::LEN + 8 (balance)
+    /// + 4 (title prefix) + 4 (description prefix) + 32 (terms_hash)
+    /// + 1 (version) + 8 (escrowed_lamports) + 8 (settle_after) + 32 (purchaser)
+    /// + 1 + PriceCurve::LEN (option) + 1 (refund_owed) + 8 (paid_lamports) + 7 (reserved)
+    ///
+    /// Title and description bytes are added by `space()` so accounts are sized to content
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 8 + 1 + 1 + 1 + 1 + 8 + 1 + 32 + 1 + 32 + 8
+        + 8 + 8 + 8 + 8
+        + 1 + RedemptionSchedule::LEN
+        + 8 + 8
+        + DiscountKind::LEN + 8
+        + 4 + 4 + 32
+        + 1 + 8 + 8 + 32
+        + 1 + PriceCurve::LEN + 1 + 8 + 7;
+
+    pub const CURRENT_VERSION: u8 = 2;
+    pub const MAX_TITLE_LEN: usize = 64;
+    pub const MAX_DESCRIPTION_LEN: usize = 256;
+
+    /// Space needed for a coupon with the given title and description
+    pub fn space(title: &str, description: &str) -> usize {
+        Self::LEN + title.len() + description.len()
+    }
+
+    /// Largest possible account size (title and description at their limits)
+    pub const MAX_LEN: usize = Self::LEN + Self::MAX_TITLE_LEN + Self::MAX_DESCRIPTION_LEN;
+
+    /// Primary sale price at `now` (follows the price curve when one is set)
+    pub fn current_price(&self, now: i64) -> u64 {
+        match &self.price_curve {
+            Some(curve) => curve.price_at(now),
+            None => self.price,
+        }
+    }
+
+    /// Release escrowed proceeds to the merchant now that the coupon was honored
+    pub fn release_on_redemption(&mut self, now: i64) {
+        if self.escrowed_lamports > 0 && self.settle_after > now {
+            self.settle_after = now;
+        }
+    }
+
+    /// Cancelled by the merchant before ever being honored - escrow is owed to the holder
+    pub fn is_refundable(&self) -> bool {
+        self.refund_owed && self.escrowed_lamports > 0
+    }
+
+    /// Activate/deactivate the coupon. Deactivating a paid, never-redeemed coupon
+    /// cancels it for good, so the merchant can't reactivate it to withdraw the escrow.
+    ///
+    /// The full price paid is escrowed for the refund. Returns the part not already
+    /// held in the RevenueVault (direct-mode sales, or deferred proceeds already
+    /// withdrawn), which the merchant must deposit there.
+    pub fn set_active(&mut self, is_active: bool) -> Result<u64> {
+        let mut refund_shortfall = 0;
+        if is_active {
+            require!(!self.refund_owed, CouponError::CouponCancelled);
+        } else if !self.refund_owed && self.last_redeemed_at == 0 {
+            let owed = self.paid_lamports.max(self.escrowed_lamports);
+            if owed > 0 {
+                refund_shortfall = owed - self.escrowed_lamports;
+                self.escrowed_lamports = owed;
+                self.refund_owed = true;
+            }
+        }
+        self.is_active = is_active;
+        Ok(refund_shortfall)
+    }
+
+    /// Whether claims/purchases are restricted to an allowlist
+    pub fn has_allowlist(&self) -> bool {
+        self.allowlist_root != [0u8; 32]
+    }
+
+    /// Ensure `now` falls inside the sale window (claim/purchase)
+    pub fn require_on_sale(&self, now: i64) -> Result<()> {
+        require!(
+            self.sale_start == 0 || now >= self.sale_start,
+            CouponError::SaleNotStarted
+        );
+        require!(
+            self.sale_end == 0 || now < self.sale_end,
+            CouponError::SaleEnded
+        );
+        Ok(())
+    }
+
+    /// Ensure the redemption cooldown has elapsed since the last redemption
+    pub fn require_cooldown_elapsed(&self, now: i64) -> Result<()> {
+        if self.min_seconds_between_redemptions == 0 || self.last_redeemed_at == 0 {
+            return Ok(());
+        }
+        let next_allowed = self
+            .last_redeemed_at
+            .checked_add(self.min_seconds_between_redemptions)
+            .ok_or(CouponError::ArithmeticOverflow)?;
+        require!(now >= next_allowed, CouponError::RedemptionCooldownActive);
+        Ok(())
+    }
+
+    /// Ensure the coupon is still worth trading: active, unexpired and not used up
+    ///
+    /// claim/purchase already count against `redemptions_remaining`, so a held
+    /// single-use coupon reads 0 before its first redemption; only coupons that
+    /// were actually redeemed down to nothing are treated as exhausted.
+    pub fn require_tradeable(&self, now: i64) -> Result<()> {
+        require!(self.is_active, CouponError::CouponInactive);
+        require!(self.expiry_date > now, CouponError::CouponExpired);
+        let exhausted = match self.discount_kind {
+            DiscountKind::StoredValue { .. } => self.balance == 0,
+            _ => self.last_redeemed_at != 0 && self.redemptions_remaining == 0,
+        };
+        require!(!exhausted, CouponError::CouponFullyRedeemed);
+        Ok(())
+    }
+
+    /// redeem_coupon calls the current holder can still make. Claim/purchase already
+    /// took one of `max_redemptions` off `redemptions_remaining`, so that counter is
+    /// uses left as-is. Stored-value coupons are spent by amount (see `balance`): 0.
+    pub fn uses_left(&self) -> u8 {
+        match self.discount_kind {
+            DiscountKind::StoredValue { .. } => 0,
+            _ => self.redemptions_remaining,
+        }
+    }
+
+    /// Ensure `now` falls inside the redemption window
+    pub fn require_redemption_open(&self, now: i64) -> Result<()> {
+        require!(
+            self.redeem_start == 0 || now >= self.redeem_start,
+            CouponError::RedemptionNotOpen
+        );
+        require!(
+            self.redeem_end == 0 || now < self.redeem_end,
+            CouponError::RedemptionClosed
+        );
+        Ok(())
+    }
+}
+
+/// Allowlist receipt - PDA tracking how many coupons a wallet has taken from an allowlisted drop
+/// Seeds: ["allowlist_receipt", allowlist_root, wallet]
+/// Scoped by root so the quota spans every coupon sharing the same allowlist
+#[account]
+pub struct AllowlistReceipt {
+    /// Wallet this receipt belongs to
+    pub wallet: Pubkey,
+    /// Coupons claimed or purchased under this allowlist
+    pub count: u16,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl AllowlistReceipt {
+    /// 8 (discriminator) + 32 (wallet) + 2 (u16) + 1 (bump)
+    pub const LEN: usize = 8 + 32 + 2 + 1;
+}
+
+/// Resale listing terms for an NFT held in the Resale Escrow PDA
+/// Seeds: ["listing", nft_mint, seller]
+#[account]
+pub struct ResaleListing {
+    /// Listed coupon NFT mint
+    pub mint: Pubkey,
+    /// Seller - receives the proceeds
+    pub seller: Pubkey,
+    /// Asking price in lamports
+    pub price: u64,
+    /// Listing can't be purchased from this time and may be cranked (Unix timestamp)
+    pub expires_at: i64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl ResaleListing {
+    /// Paid from the listing rent to whoever cranks an expired listing
+    pub const CRANK_BOUNTY_LAMPORTS: u64 = 100_000;
+
+    /// 8 (discriminator) + 32 (mint) + 32 (seller) + 8 (price) + 8 (expires_at) + 1 (bump)
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 1;
+}
+
+/// Several coupon NFTs sold together for one price
+/// Seeds: ["bundle", seller, bundle_id (u64 LE)]
+///
+/// Each NFT is held in the bundle PDA's associated token account.
+#[account]
+pub struct BundleListing {
+    /// Seller - receives the proceeds
+    pub seller: Pubkey,
+    /// Seller-chosen id so one wallet can list several bundles
+    pub bundle_id: u64,
+    /// Asking price for the whole bundle in lamports
+    pub price: u64,
+    /// Coupon NFT mints in the bundle (order is the remaining_accounts order)
+    pub mints: Vec<Pubkey>,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl BundleListing {
+    /// Large bundles need an address lookup table to fit in one transaction
+    pub const MAX_COUPONS: usize = 8;
+    /// remaining_accounts per coupon: coupon_data, merchant, source, destination
+    pub const ACCOUNTS_PER_COUPON: usize = 4;
+
+    /// 8 (discriminator) + 32 (seller) + 8 (bundle_id) + 8 (price)
+    /// + 4 + 32 * MAX_COUPONS (mints) + 1 (bump)
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 4 + 32 * Self::MAX_COUPONS + 1;
+}
+
+/// Standing offer (bid) on a specific coupon NFT, not necessarily listed
+/// Seeds: ["offer", nft_mint, bidder]
+///
+/// The offered lamports are held in this account on top of its rent.
+#[account]
+pub struct Offer {
+    /// Coupon NFT mint the offer is for
+    pub mint: Pubkey,
+    /// Wallet that placed the offer (receives the NFT)
+    pub bidder: Pubkey,
+    /// Offered price in lamports (locked in this account)
+    pub amount: u64,
+    /// Offer can't be accepted from this time (Unix timestamp)
+    pub expires_at: i64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl Offer {
+    /// 8 (discriminator) + 32 (mint) + 32 (bidder) + 8 (amount) + 8 (expires_at) + 1 (bump)
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 1;
+}
+
+/// Floor bid on any coupon from a merchant matching optional terms
+/// Seeds: ["collection_offer", merchant, bidder, offer_id (u64 LE)]
+///
+/// Filled once by any holder of a matching coupon; the offered lamports
+/// are held in this account on top of its rent.
+#[account]
+pub struct CollectionOffer {
+    /// Merchant PDA whose coupons this offer accepts
+    pub merchant: Pubkey,
+    /// Wallet that placed the offer (receives the NFT)
+    pub bidder: Pubkey,
+    /// Bidder-chosen id so one wallet can hold several offers per merchant
+    pub offer_id: u64,
+    /// Offered price in lamports (locked in this account)
+    pub amount: u64,
+    /// Offer can't be accepted from this time (Unix timestamp)
+    pub expires_at: i64,
+    /// Required coupon category (None = any category)
+    pub category: Option<CouponCategory>,
+    /// Minimum discount percentage the coupon must carry (0 = any discount)
+    pub min_discount_percentage: u8,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl CollectionOffer {
+    /// 8 (discriminator) + 32 (merchant) + 32 (bidder) + 8 (offer_id) + 8 (amount)
+    /// + 8 (expires_at) + 2 (option enum) + 1 (u8) + 1 (bump)
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 8 + 2 + 1 + 1;
+
+    /// Whether `coupon_data` satisfies this offer's merchant and terms
+    pub fn matches(&self, coupon_data: &CouponData) -> bool {
+        coupon_data.merchant == self.merchant
+            && (self.category.is_none() || self.category == Some(coupon_data.category))
+            && coupon_data.discount_percentage >= self.min_discount_percentage
+    }
+}
+
+/// English auction for a coupon NFT (primary sale or resale)
+/// Seeds: ["auction", nft_mint]
+///
+/// The NFT sits in the auction escrow token account ["auction_escrow", nft_mint]
+/// and the top bid is held in this account on top of its rent.
+#[account]
+pub struct Auction {
+    /// Coupon NFT mint being auctioned
+    pub mint: Pubkey,
+    /// Merchant PDA the coupon belongs to
+    pub merchant: Pubkey,
+    /// Wallet that created the auction - receives proceeds (minus fee) and rent
+    pub seller: Pubkey,
+    /// true = primary sale out of nft_escrow, false = resale by a holder
+    pub is_primary: bool,
+    /// Minimum acceptable first bid in lamports
+    pub reserve_price: u64,
+    /// Minimum raise over the current top bid in lamports
+    pub min_increment: u64,
+    /// Bidding closes at this time (extended by late bids)
+    pub end_time: i64,
+    /// Coupon expiry - late-bid extensions never push `end_time` up to it
+    pub coupon_expiry: i64,
+    /// Current top bidder (default = no bids yet)
+    pub highest_bidder: Pubkey,
+    /// Current top bid in lamports (0 = no bids yet)
+    pub highest_bid: u64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl Auction {
+    /// Bids within this many seconds of the end push the end out to now + this
+    pub const ANTI_SNIPE_SECONDS: i64 = 300;
+
+    /// 8 (discriminator) + 32 (mint) + 32 (merchant) + 32 (seller) + 1 (bool)
+    /// + 8 (reserve) + 8 (increment) + 8 (end_time) + 8 (coupon_expiry)
+    /// + 32 (bidder) + 8 (bid) + 1 (bump)
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 1 + 8 + 8 + 8 + 8 + 32 + 8 + 1;
+
+    /// Smallest bid that would currently be accepted
+    pub fn min_next_bid(&self) -> Result<u64> {
+        if self.highest_bid == 0 {
+            Ok(self.reserve_price.max(1))
+        } else {
+            Ok(self
+                .highest_bid
+                .checked_add(self.min_increment)
+                .ok_or(CouponError::ArithmeticOverflow)?)
+        }
+    }
+}
+
+/// Per-wallet redemption record at a merchant - lets `unique_redeemers` count each wallet once
+/// Seeds: ["redeemer", merchant, wallet]
+///
+/// Created and paid for by the claimer/buyer in claim_coupon/purchase_coupon, so
+/// redeeming never costs rent; holders who only bought on the secondary market
+/// create it on their first redemption at the merchant.
+#[account]
+pub struct RedeemerRecord {
+    /// Wallet that redeems
+    pub wallet: Pubkey,
+    /// Merchant redeemed at
+    pub merchant: Pubkey,
+    /// Redemptions by this wallet at this merchant
+    pub redemptions: u64,
+    /// First redemption timestamp (0 = never redeemed)
+    pub first_redeemed_at: i64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl RedeemerRecord {
+    /// 8 (discriminator) + 32 (wallet) + 32 (merchant) + 8 (u64) + 8 (i64) + 1 (bump)
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 1;
+
+    /// Fill in a freshly created record (no-op for an existing one)
+    pub fn init(&mut self, wallet: Pubkey, merchant: Pubkey, bump: u8) {
+        if self.wallet == Pubkey::default() {
+            self.wallet = wallet;
+            self.merchant = merchant;
+            self.bump = bump;
+        }
+    }
+
+    /// Record a redemption, returning true if it is the wallet's first at this merchant
+    pub fn record(&mut self, now: i64) -> Result<bool> {
+        let first = self.first_redeemed_at == 0;
+        if first {
+            self.first_redeemed_at = now;
+        }
+        self.redemptions = self
+            .redemptions
+            .checked_add(1)
+            .ok_or(CouponError::ArithmeticOverflow)?;
+        Ok(first)
+    }
+}
+
+/// Merkle proof presented by a caller of an allowlisted coupon
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AllowlistProof {
+    /// Sibling hashes from leaf to root
+    pub proof: Vec<[u8; 32]>,
+    /// Per-wallet quota encoded in the leaf (0 = unlimited)
+    pub quota: u16,
+}
+
+/// Discount type applied at the point of sale
+/// Amounts are in the smallest unit of `currency` (ISO 4217 code, e.g. b"USD" in cents)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DiscountKind {
+    /// Percentage off (1-100)
+    Percentage { pct: u8 },
+    /// Fixed amount off, e.g. $10 off
+    FixedAmount { currency: [u8; 3], amount: u64 },
+    /// Buy one get one free
+    Bogo,
+    /// Free item identified by the hash of the merchant's SKU
+    FreeItem { sku_hash: [u8; 32] },
+    /// Percentage off up to a maximum amount, e.g. 20% off up to $50
+    PercentageCapped { pct: u8, cap: u64, currency: [u8; 3] },
+    /// Gift card holding `amount` of stored value, spent via redeem_amount
+    StoredValue { currency: [u8; 3], amount: u64 },
+}
+
+impl DiscountKind {
+    /// 1 (variant) + 32 (largest payload: FreeItem sku_hash)
+    pub const LEN: usize = 1 + 32;
+
+    pub fn is_valid(&self) -> bool {
+        let valid_pct = |pct: u8| pct > 0 && pct <= 100;
+        let valid_currency = |currency: &[u8; 3]| currency.iter().all(u8::is_ascii_uppercase);
+        match self {
+            DiscountKind::Percentage { pct } => valid_pct(*pct),
+            DiscountKind::FixedAmount { currency, amount } => {
+                valid_currency(currency) && *amount > 0
+            }
+            DiscountKind::Bogo => true,
+            DiscountKind::FreeItem { sku_hash } => *sku_hash != [0u8; 32],
+            DiscountKind::PercentageCapped { pct, cap, currency } => {
+                valid_pct(*pct) && *cap > 0 && valid_currency(currency)
+            }
+            DiscountKind::StoredValue { currency, amount } => {
+                valid_currency(currency) && *amount > 0
+            }
+        }
+    }
+
+    /// Percentage component for percentage-based kinds, 0 otherwise
+    pub fn percentage(&self) -> u8 {
+        match self {
+            DiscountKind::Percentage { pct } | DiscountKind::PercentageCapped { pct, .. } => *pct,
+            _ => 0,
+        }
+    }
+
+    /// Initial balance for stored-value coupons, 0 otherwise
+    pub fn initial_balance(&self) -> u64 {
+        match self {
+            DiscountKind::StoredValue { amount, .. } => *amount,
+            _ => 0,
+        }
+    }
+}
+
+/// Dutch-auction price curve: price falls linearly from `start_price` to
+/// `end_price` between `start_time` and `end_time`, in `step_seconds` steps
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct PriceCurve {
+    /// Price in lamports at and before `start_time`
+    pub start_price: u64,
+    /// Floor price in lamports at and after `end_time`
+    pub end_price: u64,
+    /// Decline starts (Unix timestamp)
+    pub start_time: i64,
+    /// Decline ends (Unix timestamp)
+    pub end_time: i64,
+    /// Price drops once per step (0 = continuous decline)
+    pub step_seconds: i64,
+}
+
+impl PriceCurve {
+    /// 8 (start_price) + 8 (end_price) + 8 (start_time) + 8 (end_time) + 8 (step_seconds)
+    pub const LEN: usize = 8 + 8 + 8 + 8 + 8;
+
+    pub fn is_valid(&self) -> bool {
+        self.end_price > 0
+            && self.start_price >= self.end_price
+            && self.start_time >= 0
+            && self.start_time < self.end_time
+            && self.end_time.checked_sub(self.start_time).is_some()
+            && self.step_seconds >= 0
+    }
+
+    /// Price in lamports at `now`
+    pub fn price_at(&self, now: i64) -> u64 {
+        if now <= self.start_time {
+            return self.start_price;
+        }
+        if now >= self.end_time {
+            return self.end_price;
+        }
+
+        // start_time < now < end_time here, so neither difference can go negative
+        let mut elapsed = now.saturating_sub(self.start_time);
+        if self.step_seconds > 0 {
+            elapsed -= elapsed % self.step_seconds;
+        }
+        let duration = self.end_time.saturating_sub(self.start_time);
+        let drop = u128::from(self.start_price - self.end_price) * elapsed as u128
+            / duration as u128;
+        self.start_price - drop as u64
+    }
+}
+
+/// Recurring weekly redemption hours, evaluated against the on-chain clock
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct RedemptionSchedule {
+    /// Allowed weekdays: bit 0 = Monday ... bit 6 = Sunday
+    pub weekday_mask: u8,
+    /// Window start in minutes after local midnight (0-1439)
+    pub start_minute: u16,
+    /// Window end in minutes after local midnight, exclusive (0-1439)
+    /// If end < start the window runs past midnight and belongs to the start day
+    pub end_minute: u16,
+    /// Merchant's offset from UTC in minutes (e.g. -300 for UTC-5)
+    pub utc_offset_minutes: i16,
+}
+
+impl RedemptionSchedule {
+    /// 1 (weekday_mask) + 2 (start_minute) + 2 (end_minute) + 2 (utc_offset_minutes)
+    pub const LEN: usize = 1 + 2 + 2 + 2;
+
+    pub const MINUTES_PER_DAY: u16 = 24 * 60;
+    /// Real-world UTC offsets range from -12:00 to +14:00
+    pub const MIN_UTC_OFFSET_MINUTES: i16 = -12 * 60;
+    pub const MAX_UTC_OFFSET_MINUTES: i16 = 14 * 60;
+
+    pub fn is_valid(&self) -> bool {
+        self.weekday_mask != 0
+            && self.weekday_mask < 0x80
+            && self.start_minute < Self::MINUTES_PER_DAY
+            && self.end_minute < Self::MINUTES_PER_DAY
+            && self.start_minute != self.end_minute
+            && (Self::MIN_UTC_OFFSET_MINUTES..=Self::MAX_UTC_OFFSET_MINUTES)
+                .contains(&self.utc_offset_minutes)
+    }
+
+    /// Whether `unix_timestamp` falls inside the schedule
+    pub fn is_open(&self, unix_timestamp: i64) -> bool {
+        let local = unix_timestamp.saturating_add(i64::from(self.utc_offset_minutes) * 60);
+        let days = local.div_euclid(86_400);
+        let minute = (local.rem_euclid(86_400) / 60) as u16;
+        // 1970-01-01 was a Thursday (weekday 3 with Monday = 0)
+        let weekday = (days + 3).rem_euclid(7) as u8;
+        let day_allowed = |day: u8| self.weekday_mask & (1 << day) != 0;
+
+        if self.start_minute < self.end_minute {
+            day_allowed(weekday) && minute >= self.start_minute && minute < self.end_minute
+        } else if minute >= self.start_minute {
+            // Overnight window, before midnight: belongs to today
+            day_allowed(weekday)
+        } else if minute < self.end_minute {
+            // Overnight window, after midnight: belongs to yesterday
+            day_allowed((weekday + 6) % 7)
+        } else {
+            false
+        }
+    }
+}
+
+/// Holding requirement for claiming/purchasing a token-gated coupon
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GateKind {
+    /// No holding requirement
+    #[default]
+    None,
+    /// Caller must hold at least `gate_min_amount` of `gate_mint` (e.g. DEAL token)
+    TokenBalance,
+    /// Caller must hold an NFT from the verified collection `gate_mint`
+    Collection,
 }
 
 /// Coupon categories for filtering and organization
@@ -70,3 +841,134 @@ impl Default for CouponCategory {
         CouponCategory::Other
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve(step_seconds: i64) -> PriceCurve {
+        PriceCurve {
+            start_price: 1_000,
+            end_price: 100,
+            start_time: 1_000,
+            end_time: 1_900,
+            step_seconds,
+        }
+    }
+
+    #[test]
+    fn price_curve_before_start_is_start_price() {
+        assert_eq!(curve(0).price_at(0), 1_000);
+        assert_eq!(curve(0).price_at(1_000), 1_000);
+        assert_eq!(curve(0).price_at(i64::MIN), 1_000);
+    }
+
+    #[test]
+    fn price_curve_after_end_is_end_price() {
+        assert_eq!(curve(0).price_at(1_900), 100);
+        assert_eq!(curve(0).price_at(i64::MAX), 100);
+    }
+
+    #[test]
+    fn price_curve_continuous_decline() {
+        let c = curve(0);
+        assert_eq!(c.price_at(1_001), 999);
+        assert_eq!(c.price_at(1_450), 550);
+        assert_eq!(c.price_at(1_899), 101);
+    }
+
+    #[test]
+    fn price_curve_stepped_decline() {
+        let c = curve(300);
+        assert_eq!(c.price_at(1_299), 1_000);
+        assert_eq!(c.price_at(1_300), 700);
+        assert_eq!(c.price_at(1_599), 700);
+        assert_eq!(c.price_at(1_600), 400);
+        assert_eq!(c.price_at(1_899), 400);
+    }
+
+    #[test]
+    fn price_curve_rejects_overflowing_span() {
+        let mut c = curve(0);
+        c.start_time = -1;
+        assert!(!c.is_valid());
+        c.start_time = 0;
+        c.end_time = i64::MAX;
+        assert!(c.is_valid());
+        assert_eq!(c.price_at(i64::MAX - 1), 101);
+    }
+
+    /// Monday 1970-01-05 00:00 UTC
+    const MONDAY: i64 = 4 * 86_400;
+    const HOUR: i64 = 3_600;
+    const DAY: i64 = 86_400;
+
+    /// Mon-Fri 09:00-17:00
+    fn office_hours(utc_offset_minutes: i16) -> RedemptionSchedule {
+        RedemptionSchedule {
+            weekday_mask: 0b001_1111,
+            start_minute: 9 * 60,
+            end_minute: 17 * 60,
+            utc_offset_minutes,
+        }
+    }
+
+    #[test]
+    fn schedule_start_inclusive_end_exclusive() {
+        let s = office_hours(0);
+        assert!(!s.is_open(MONDAY + 9 * HOUR - 60));
+        assert!(s.is_open(MONDAY + 9 * HOUR));
+        assert!(s.is_open(MONDAY + 17 * HOUR - 60));
+        assert!(!s.is_open(MONDAY + 17 * HOUR));
+    }
+
+    #[test]
+    fn schedule_closed_days() {
+        let s = office_hours(0);
+        assert!(s.is_open(MONDAY + 4 * DAY + 10 * HOUR)); // Friday
+        assert!(!s.is_open(MONDAY + 5 * DAY + 10 * HOUR)); // Saturday
+        assert!(!s.is_open(MONDAY + 6 * DAY + 10 * HOUR)); // Sunday
+    }
+
+    #[test]
+    fn schedule_applies_utc_offset() {
+        // UTC-5: 09:00 local is 14:00 UTC
+        let s = office_hours(-300);
+        assert!(!s.is_open(MONDAY + 9 * HOUR));
+        assert!(s.is_open(MONDAY + 14 * HOUR));
+        // Saturday 03:00 UTC is still Friday 22:00 local, after closing
+        assert!(!s.is_open(MONDAY + 5 * DAY + 3 * HOUR));
+    }
+
+    #[test]
+    fn schedule_overnight_window_belongs_to_start_day() {
+        // Friday only, 22:00-02:00
+        let s = RedemptionSchedule {
+            weekday_mask: 1 << 4,
+            start_minute: 22 * 60,
+            end_minute: 2 * 60,
+            utc_offset_minutes: 0,
+        };
+        let friday = MONDAY + 4 * DAY;
+        assert!(s.is_open(friday + 22 * HOUR));
+        assert!(s.is_open(friday + DAY + HOUR)); // Saturday 01:00
+        assert!(!s.is_open(friday + DAY + 2 * HOUR)); // Saturday 02:00
+        assert!(!s.is_open(friday + HOUR)); // Friday 01:00 belongs to Thursday
+        assert!(!s.is_open(friday + DAY + 22 * HOUR)); // Saturday night
+    }
+
+    #[test]
+    fn schedule_pre_epoch_timestamps() {
+        let s = office_hours(0);
+        // Wednesday 1969-12-31 and Monday 1969-12-29
+        assert!(s.is_open(-DAY + 10 * HOUR));
+        assert!(s.is_open(-3 * DAY + 9 * HOUR));
+        // Sunday 1969-12-28
+        assert!(!s.is_open(-4 * DAY + 10 * HOUR));
+        // Extreme timestamps must not overflow
+        s.is_open(i64::MIN);
+        s.is_open(i64::MAX);
+        office_hours(RedemptionSchedule::MIN_UTC_OFFSET_MINUTES).is_open(i64::MIN);
+        office_hours(RedemptionSchedule::MAX_UTC_OFFSET_MINUTES).is_open(i64::MAX);
+    }
+}
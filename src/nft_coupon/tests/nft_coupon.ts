@@ -2,28 +2,63 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program, BN } from "@coral-xyz/anchor";
 import { NftCoupon } from "../target/types/nft_coupon";
 import {
+  ComputeBudgetProgram,
   Keypair,
+  LAMPORTS_PER_SOL,
   PublicKey,
   SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
   SYSVAR_RENT_PUBKEY,
+  Transaction,
 } from "@solana/web3.js";
 import {
   TOKEN_PROGRAM_ID,
   ASSOCIATED_TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccountIdempotentInstruction,
+  createMint,
   getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
 } from "@solana/spl-token";
+import { createHash } from "crypto";
 import { expect } from "chai";
 
 // Metaplex Token Metadata Program ID
 const TOKEN_METADATA_PROGRAM_ID = new PublicKey(
   "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
 );
+const BPF_LOADER_UPGRADEABLE_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
+
+// Every fee-charging path splits 97.5% seller / 2.5% platform
+const platformFee = (price: number) => Math.floor((price * 25) / 1000);
+// Slack for transaction fees paid by a signer whose balance is compared
+const TX_FEE_SLACK = 20_000;
+
+type Coupon = {
+  mint: PublicKey;
+  couponData: PublicKey;
+  nftEscrow: PublicKey;
+  merchant: PublicKey;
+};
+
+type CouponOptions = {
+  title?: string;
+  description?: string;
+  discountKind?: object;
+  expiryDate?: BN;
+  category?: object;
+  maxRedemptions?: number;
+  price?: number;
+};
 
 describe("nft_coupon", () => {
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
 
   const program = anchor.workspace.NftCoupon as Program<NftCoupon>;
+  const connection = provider.connection;
 
   // Test accounts
   let merchantAuthority: Keypair;
@@ -32,56 +67,544 @@ describe("nft_coupon", () => {
 
   let nftMint: Keypair;
   let couponDataPda: PublicKey;
-  let couponDataBump: number;
   let metadataAccount: PublicKey;
-  let merchantTokenAccount: PublicKey;
+  let nftEscrowPda: PublicKey;
 
   let userWallet: Keypair;
   let userTokenAccount: PublicKey;
 
+  // Second buyer / bidder for marketplace flows
+  let otherWallet: Keypair;
+  // Receives platform fees (PlatformConfig.fee_wallet)
+  let feeWallet: Keypair;
+  let platformConfigPda: PublicKey;
+
   const businessName = "Test Coffee Shop";
   const couponTitle = "50% Off Coffee";
   const couponDescription = "Get 50% off all specialty coffee drinks";
   const discountPercentage = 50;
-  const maxRedemptions = 1;
+  const maxRedemptions = 2;
   const metadataUri = "https://arweave.net/test-metadata";
+  const termsHash = Array(32).fill(0);
 
-  before(async () => {
-    // Initialize test accounts
-    merchantAuthority = Keypair.generate();
-    userWallet = Keypair.generate();
+  // ---------------------------------------------------------------------
+  // Helpers
+  // ---------------------------------------------------------------------
+
+  const pda = (seeds: (Buffer | Uint8Array)[]) =>
+    PublicKey.findProgramAddressSync(seeds, program.programId)[0];
 
-    // Airdrop SOL to test accounts
-    const airdropSignature = await provider.connection.requestAirdrop(
-      merchantAuthority.publicKey,
-      10 * anchor.web3.LAMPORTS_PER_SOL
+  const u64Le = (value: number) => new BN(value).toArrayLike(Buffer, "le", 8);
+
+  const merchantPdaFor = (authority: PublicKey) =>
+    pda([Buffer.from("merchant"), authority.toBuffer()]);
+  const couponPdaFor = (mint: PublicKey) =>
+    pda([Buffer.from("coupon"), mint.toBuffer()]);
+  const escrowPdaFor = (merchant: PublicKey, mint: PublicKey) =>
+    pda([Buffer.from("nft_escrow"), merchant.toBuffer(), mint.toBuffer()]);
+  const revenueVaultFor = (merchant: PublicKey) =>
+    pda([Buffer.from("revenue_vault"), merchant.toBuffer()]);
+  const redeemerRecordFor = (merchant: PublicKey, wallet: PublicKey) =>
+    pda([Buffer.from("redeemer"), merchant.toBuffer(), wallet.toBuffer()]);
+  const resaleEscrowFor = (mint: PublicKey, seller: PublicKey) =>
+    pda([Buffer.from("resale_escrow"), mint.toBuffer(), seller.toBuffer()]);
+  const listingFor = (mint: PublicKey, seller: PublicKey) =>
+    pda([Buffer.from("listing"), mint.toBuffer(), seller.toBuffer()]);
+  const offerFor = (mint: PublicKey, bidder: PublicKey) =>
+    pda([Buffer.from("offer"), mint.toBuffer(), bidder.toBuffer()]);
+  const collectionOfferFor = (
+    merchant: PublicKey,
+    bidder: PublicKey,
+    offerId: number
+  ) =>
+    pda([
+      Buffer.from("collection_offer"),
+      merchant.toBuffer(),
+      bidder.toBuffer(),
+      u64Le(offerId),
+    ]);
+  const auctionFor = (mint: PublicKey) =>
+    pda([Buffer.from("auction"), mint.toBuffer()]);
+  const auctionEscrowFor = (mint: PublicKey) =>
+    pda([Buffer.from("auction_escrow"), mint.toBuffer()]);
+  const bundleFor = (seller: PublicKey, bundleId: number) =>
+    pda([Buffer.from("bundle"), seller.toBuffer(), u64Le(bundleId)]);
+
+  const metadataPdaFor = (mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        mint.toBuffer(),
+      ],
+      TOKEN_METADATA_PROGRAM_ID
+    )[0];
+  const masterEditionPdaFor = (mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        mint.toBuffer(),
+        Buffer.from("edition"),
+      ],
+      TOKEN_METADATA_PROGRAM_ID
+    )[0];
+
+  const programDataPda = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    BPF_LOADER_UPGRADEABLE_ID
+  )[0];
+
+  const ata = (mint: PublicKey, owner: PublicKey, offCurve = false) =>
+    getAssociatedTokenAddressSync(mint, owner, offCurve);
+
+  async function airdrop(to: PublicKey, sol: number) {
+    const signature = await connection.requestAirdrop(
+      to,
+      sol * LAMPORTS_PER_SOL
     );
-    await provider.connection.confirmTransaction(airdropSignature);
+    await connection.confirmTransaction(signature);
+  }
+
+  async function newFundedWallet(sol = 10) {
+    const wallet = Keypair.generate();
+    await airdrop(wallet.publicKey, sol);
+    return wallet;
+  }
+
+  const balance = (account: PublicKey) => connection.getBalance(account);
+
+  async function tokenBalance(account: PublicKey) {
+    try {
+      const result = await connection.getTokenAccountBalance(account);
+      return Number(result.value.amount);
+    } catch {
+      // Closed or never created
+      return 0;
+    }
+  }
 
-    const userAirdrop = await provider.connection.requestAirdrop(
-      userWallet.publicKey,
-      5 * anchor.web3.LAMPORTS_PER_SOL
+  // Unix time according to the validator's clock
+  async function chainNow() {
+    const slot = await connection.getSlot();
+    return (await connection.getBlockTime(slot)) as number;
+  }
+
+  const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+  async function waitUntil(timestamp: number) {
+    while ((await chainNow()) < timestamp) {
+      await sleep(1000);
+    }
+  }
+
+  // Fails the test unless `promise` rejects (with `code` in the message, if given)
+  async function expectError(promise: Promise<unknown>, code?: string) {
+    try {
+      await promise;
+    } catch (error) {
+      if (code) {
+        expect(error.toString()).to.include(code);
+      }
+      return;
+    }
+    expect.fail(`Should have thrown ${code ?? "an error"}`);
+  }
+
+  // Anchor events emitted by a confirmed transaction
+  async function eventsOf(signature: string) {
+    const tx = await connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    return [...parser.parseLogs(tx?.meta?.logMessages ?? [])];
+  }
+
+  async function ensureAta(
+    payer: Keypair,
+    mint: PublicKey,
+    owner: PublicKey,
+    offCurve = false
+  ) {
+    const address = ata(mint, owner, offCurve);
+    const tx = new Transaction().add(
+      createAssociatedTokenAccountIdempotentInstruction(
+        payer.publicKey,
+        address,
+        owner,
+        mint
+      )
     );
-    await provider.connection.confirmTransaction(userAirdrop);
+    await provider.sendAndConfirm(tx, [payer]);
+    return address;
+  }
+
+  async function initializeMerchant(authority: Keypair, name: string) {
+    const merchant = merchantPdaFor(authority.publicKey);
+    await program.methods
+      .initializeMerchant(name)
+      .accountsPartial({
+        merchant,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    return merchant;
+  }
+
+  function createCouponBuilder(
+    authority: Keypair,
+    mint: Keypair,
+    options: CouponOptions = {}
+  ) {
+    const merchant = merchantPdaFor(authority.publicKey);
+    return program.methods
+      .createCoupon(
+        options.title ?? couponTitle,
+        options.description ?? couponDescription,
+        (options.discountKind ?? {
+          percentage: { pct: discountPercentage },
+        }) as any,
+        options.expiryDate ?? new BN(Math.floor(Date.now() / 1000) + 86400 * 30),
+        (options.category ?? { foodAndBeverage: {} }) as any,
+        options.maxRedemptions ?? maxRedemptions,
+        metadataUri,
+        new BN(options.price ?? 0),
+        termsHash
+      )
+      .accountsPartial({
+        merchant,
+        platformConfig: platformConfigPda,
+        couponData: couponPdaFor(mint.publicKey),
+        merchantTokenAccount: ata(mint.publicKey, authority.publicKey),
+        nftEscrow: escrowPdaFor(merchant, mint.publicKey),
+        nftMint: mint.publicKey,
+        metadataAccount: metadataPdaFor(mint.publicKey),
+        masterEdition: masterEditionPdaFor(mint.publicKey),
+        merchantAuthority: authority.publicKey,
+        authority: authority.publicKey,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .preInstructions([
+        ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+      ])
+      .signers([authority, mint]);
+  }
+
+  async function createCoupon(
+    authority: Keypair,
+    options: CouponOptions = {}
+  ): Promise<Coupon> {
+    const mint = Keypair.generate();
+    await createCouponBuilder(authority, mint, options).rpc();
+    const merchant = merchantPdaFor(authority.publicKey);
+    return {
+      mint: mint.publicKey,
+      couponData: couponPdaFor(mint.publicKey),
+      nftEscrow: escrowPdaFor(merchant, mint.publicKey),
+      merchant,
+    };
+  }
+
+  function claimBuilder(coupon: Coupon, user: Keypair) {
+    return program.methods
+      .claimCoupon(null)
+      .accountsPartial({
+        couponData: coupon.couponData,
+        merchant: coupon.merchant,
+        nftEscrow: coupon.nftEscrow,
+        nftMint: coupon.mint,
+        userTokenAccount: ata(coupon.mint, user.publicKey),
+        user: user.publicKey,
+        allowlistReceipt: null,
+        redeemerRecord: redeemerRecordFor(coupon.merchant, user.publicKey),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user]);
+  }
+
+  function purchaseBuilder(
+    coupon: Coupon,
+    buyer: Keypair,
+    merchantAuthorityKey: PublicKey,
+    maxPrice: number,
+    deferred = false
+  ) {
+    return program.methods
+      .purchaseCoupon(null, new BN(maxPrice))
+      .accountsPartial({
+        couponData: coupon.couponData,
+        merchant: coupon.merchant,
+        merchantAuthority: merchantAuthorityKey,
+        platformWallet: feeWallet.publicKey,
+        nftEscrow: coupon.nftEscrow,
+        nftMint: coupon.mint,
+        buyerTokenAccount: ata(coupon.mint, buyer.publicKey),
+        buyer: buyer.publicKey,
+        allowlistReceipt: null,
+        redeemerRecord: redeemerRecordFor(coupon.merchant, buyer.publicKey),
+        revenueVault: deferred ? revenueVaultFor(coupon.merchant) : null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([buyer]);
+  }
+
+  function redeemBuilder(coupon: Coupon, user: Keypair) {
+    return program.methods
+      .redeemCoupon()
+      .accountsPartial({
+        couponData: coupon.couponData,
+        merchant: coupon.merchant,
+        nftMint: coupon.mint,
+        nftTokenAccount: ata(coupon.mint, user.publicKey),
+        user: user.publicKey,
+        redeemerRecord: redeemerRecordFor(coupon.merchant, user.publicKey),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user]);
+  }
+
+  function couponStatusBuilder(
+    coupon: Coupon,
+    authority: Keypair,
+    isActive: boolean,
+    revenueVault: PublicKey | null = null
+  ) {
+    return program.methods
+      .updateCouponStatus(isActive)
+      .accountsPartial({
+        merchant: coupon.merchant,
+        couponData: coupon.couponData,
+        revenueVault,
+        authority: authority.publicKey,
+        merchantAuthority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority]);
+  }
+
+  // Accounts shared by the merchant-signed coupon setters
+  const couponSetterAccounts = (coupon: Coupon, authority: Keypair) => ({
+    merchant: coupon.merchant,
+    couponData: coupon.couponData,
+    authority: authority.publicKey,
+    merchantAuthority: authority.publicKey,
+  });
+
+  function listBuilder(
+    coupon: Coupon,
+    seller: Keypair,
+    price: number,
+    expiresAt: BN | null = null
+  ) {
+    return program.methods
+      .listForResale(new BN(price), expiresAt)
+      .accountsPartial({
+        nftMint: coupon.mint,
+        couponData: coupon.couponData,
+        merchant: coupon.merchant,
+        sellerTokenAccount: ata(coupon.mint, seller.publicKey),
+        resaleEscrow: resaleEscrowFor(coupon.mint, seller.publicKey),
+        listing: listingFor(coupon.mint, seller.publicKey),
+        seller: seller.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([seller]);
+  }
+
+  function purchaseFromResaleBuilder(
+    coupon: Coupon,
+    seller: PublicKey,
+    buyer: Keypair,
+    expectedPrice: number
+  ) {
+    return program.methods
+      .purchaseFromResale(new BN(expectedPrice))
+      .accountsPartial({
+        nftMint: coupon.mint,
+        couponData: coupon.couponData,
+        merchant: coupon.merchant,
+        resaleEscrow: resaleEscrowFor(coupon.mint, seller),
+        listing: listingFor(coupon.mint, seller),
+        buyerTokenAccount: ata(coupon.mint, buyer.publicKey),
+        seller,
+        buyer: buyer.publicKey,
+        platformWallet: feeWallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([buyer]);
+  }
+
+  function transferBuilder(
+    coupon: Coupon,
+    seller: Keypair,
+    buyer: Keypair,
+    price: number
+  ) {
+    return program.methods
+      .transferCoupon(new BN(price))
+      .accountsPartial({
+        nftMint: coupon.mint,
+        couponData: coupon.couponData,
+        merchant: coupon.merchant,
+        sellerTokenAccount: ata(coupon.mint, seller.publicKey),
+        buyerTokenAccount: ata(coupon.mint, buyer.publicKey),
+        seller: seller.publicKey,
+        buyer: buyer.publicKey,
+        platformWallet: feeWallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([seller, buyer]);
+  }
+
+  before(async () => {
+    // Initialize test accounts
+    merchantAuthority = await newFundedWallet(20);
+    userWallet = await newFundedWallet(10);
+    otherWallet = await newFundedWallet(10);
+    // Fee wallet starts funded so small fee transfers never fall below rent exemption
+    feeWallet = await newFundedWallet(1);
 
     // Derive merchant PDA
     [merchantPda, merchantBump] = PublicKey.findProgramAddressSync(
       [Buffer.from("merchant"), merchantAuthority.publicKey.toBuffer()],
       program.programId
     );
+    platformConfigPda = pda([Buffer.from("platform_config")]);
+
+    // create_coupon reads the platform config, so it must exist before any coupon
+    await program.methods
+      .initializePlatform(false, feeWallet.publicKey)
+      .accountsPartial({
+        platformConfig: platformConfigPda,
+        program: program.programId,
+        programData: programDataPda,
+        admin: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
   });
 
-  describe("initialize_merchant", () => {
-    it("should initialize a merchant account", async () => {
+  describe("initialize_platform", () => {
+    const programData = programDataPda;
+
+    it("should record the upgrade authority as admin and the fee wallet", async () => {
+      const config = await program.account.platformConfig.fetch(
+        platformConfigPda
+      );
+      expect(config.admin.toString()).to.equal(
+        provider.wallet.publicKey.toString()
+      );
+      expect(config.requireVerifiedMerchants).to.be.false;
+      expect(config.feeWallet.toString()).to.equal(
+        feeWallet.publicKey.toString()
+      );
+    });
+
+    it("should refuse to initialize the platform twice", async () => {
+      await expectError(
+        program.methods
+          .initializePlatform(true, feeWallet.publicKey)
+          .accountsPartial({
+            platformConfig: platformConfigPda,
+            program: program.programId,
+            programData,
+            admin: provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc()
+      );
+    });
+
+    it("should fail when the caller is not the upgrade authority", async () => {
+      const impostor = await newFundedWallet(1);
+      await expectError(
+        program.methods
+          .initializePlatform(false, impostor.publicKey)
+          .accountsPartial({
+            platformConfig: platformConfigPda,
+            program: program.programId,
+            programData,
+            admin: impostor.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([impostor])
+          .rpc()
+      );
+    });
+  });
+
+  describe("update_platform_config", () => {
+    it("should update the fee wallet (admin only)", async () => {
+      const newFeeWallet = await newFundedWallet(1);
       await program.methods
-        .initializeMerchant(businessName)
-        .accounts({
-          merchant: merchantPda,
-          authority: merchantAuthority.publicKey,
-          systemProgram: SystemProgram.programId,
+        .updatePlatformConfig(
+          provider.wallet.publicKey,
+          false,
+          newFeeWallet.publicKey
+        )
+        .accountsPartial({
+          platformConfig: platformConfigPda,
+          admin: provider.wallet.publicKey,
+        })
+        .rpc();
+      let config = await program.account.platformConfig.fetch(
+        platformConfigPda
+      );
+      expect(config.feeWallet.toString()).to.equal(
+        newFeeWallet.publicKey.toString()
+      );
+
+      // Restore the fee wallet the rest of the suite checks against
+      await program.methods
+        .updatePlatformConfig(provider.wallet.publicKey, false, feeWallet.publicKey)
+        .accountsPartial({
+          platformConfig: platformConfigPda,
+          admin: provider.wallet.publicKey,
         })
-        .signers([merchantAuthority])
         .rpc();
+      config = await program.account.platformConfig.fetch(platformConfigPda);
+      expect(config.feeWallet.toString()).to.equal(
+        feeWallet.publicKey.toString()
+      );
+    });
+
+    it("should fail for a non-admin", async () => {
+      await expectError(
+        program.methods
+          .updatePlatformConfig(userWallet.publicKey, false, userWallet.publicKey)
+          .accountsPartial({
+            platformConfig: platformConfigPda,
+            admin: userWallet.publicKey,
+          })
+          .signers([userWallet])
+          .rpc(),
+        "UnauthorizedAdmin"
+      );
+    });
+  });
+
+  describe("initialize_merchant", () => {
+    it("should initialize a merchant account", async () => {
+      await initializeMerchant(merchantAuthority, businessName);
 
       // Fetch and verify merchant account
       const merchantAccount = await program.account.merchant.fetch(merchantPda);
@@ -94,20 +617,7 @@ describe("nft_coupon", () => {
     });
 
     it("should fail to initialize duplicate merchant", async () => {
-      try {
-        await program.methods
-          .initializeMerchant("Duplicate Shop")
-          .accounts({
-            merchant: merchantPda,
-            authority: merchantAuthority.publicKey,
-            systemProgram: SystemProgram.programId,
-          })
-          .signers([merchantAuthority])
-          .rpc();
-        expect.fail("Should have thrown error for duplicate merchant");
-      } catch (error) {
-        expect(error).to.exist;
-      }
+      await expectError(initializeMerchant(merchantAuthority, "Duplicate Shop"));
     });
   });
 
@@ -115,59 +625,13 @@ describe("nft_coupon", () => {
     before(() => {
       // Generate NFT mint
       nftMint = Keypair.generate();
-
-      // Derive coupon data PDA
-      [couponDataPda, couponDataBump] = PublicKey.findProgramAddressSync(
-        [Buffer.from("coupon"), nftMint.publicKey.toBuffer()],
-        program.programId
-      );
-
-      // Derive metadata account (Metaplex standard)
-      [metadataAccount] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("metadata"),
-          TOKEN_METADATA_PROGRAM_ID.toBuffer(),
-          nftMint.publicKey.toBuffer(),
-        ],
-        TOKEN_METADATA_PROGRAM_ID
-      );
-
-      // Derive merchant's token account
-      merchantTokenAccount = getAssociatedTokenAddressSync(
-        nftMint.publicKey,
-        merchantAuthority.publicKey
-      );
+      couponDataPda = couponPdaFor(nftMint.publicKey);
+      metadataAccount = metadataPdaFor(nftMint.publicKey);
+      nftEscrowPda = escrowPdaFor(merchantPda, nftMint.publicKey);
     });
 
-    it("should create a coupon NFT with metadata", async () => {
-      const expiryDate = new BN(Date.now() / 1000 + 86400 * 30); // 30 days from now
-
-      await program.methods
-        .createCoupon(
-          couponTitle,
-          couponDescription,
-          discountPercentage,
-          expiryDate,
-          { foodAndBeverage: {} },
-          maxRedemptions,
-          metadataUri
-        )
-        .accounts({
-          merchant: merchantPda,
-          couponData: couponDataPda,
-          nftMint: nftMint.publicKey,
-          metadataAccount: metadataAccount,
-          nftTokenAccount: merchantTokenAccount,
-          merchantAuthority: merchantAuthority.publicKey,
-          authority: merchantAuthority.publicKey,
-          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
-          tokenProgram: TOKEN_PROGRAM_ID,
-          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-          systemProgram: SystemProgram.programId,
-          rent: SYSVAR_RENT_PUBKEY,
-        })
-        .signers([merchantAuthority, nftMint])
-        .rpc();
+    it("should create a coupon NFT held in the escrow PDA", async () => {
+      await createCouponBuilder(merchantAuthority, nftMint).rpc();
 
       // Fetch and verify coupon data
       const couponData = await program.account.couponData.fetch(couponDataPda);
@@ -177,437 +641,2135 @@ describe("nft_coupon", () => {
       expect(couponData.redemptionsRemaining).to.equal(maxRedemptions);
       expect(couponData.maxRedemptions).to.equal(maxRedemptions);
       expect(couponData.isActive).to.be.true;
+      expect(couponData.title).to.equal(couponTitle);
+      expect(couponData.description).to.equal(couponDescription);
 
       // Verify merchant's total coupons created incremented
       const merchantAccount = await program.account.merchant.fetch(merchantPda);
       expect(merchantAccount.totalCouponsCreated.toNumber()).to.equal(1);
 
-      // Verify NFT was minted to merchant's account
-      const tokenAccountInfo = await provider.connection.getTokenAccountBalance(
-        merchantTokenAccount
-      );
-      expect(tokenAccountInfo.value.uiAmount).to.equal(1);
+      // Verify NFT was minted into the escrow PDA
+      expect(await tokenBalance(nftEscrowPda)).to.equal(1);
+      expect(
+        (await connection.getAccountInfo(metadataAccount)) !== null
+      ).to.be.true;
     });
 
-    it("should fail with invalid discount percentage", async () => {
-      const invalidMint = Keypair.generate();
-      const [invalidCouponPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("coupon"), invalidMint.publicKey.toBuffer()],
-        program.programId
-      );
-      const [invalidMetadata] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("metadata"),
-          TOKEN_METADATA_PROGRAM_ID.toBuffer(),
-          invalidMint.publicKey.toBuffer(),
-        ],
-        TOKEN_METADATA_PROGRAM_ID
+    it("should emit the discount terms for POS integrations", async () => {
+      const mint = Keypair.generate();
+      const signature = await createCouponBuilder(merchantAuthority, mint, {
+        title: "$10 Off",
+        discountKind: {
+          fixedAmount: { currency: [85, 83, 68], amount: new BN(1000) },
+        },
+      }).rpc({ commitment: "confirmed" });
+
+      const event = (await eventsOf(signature)).find(
+        (e) => e.name === "couponCreatedEvent"
       );
-      const invalidTokenAccount = getAssociatedTokenAddressSync(
-        invalidMint.publicKey,
-        merchantAuthority.publicKey
+      expect(event, "CouponCreatedEvent").to.exist;
+      expect(event.data.nftMint.toString()).to.equal(mint.publicKey.toString());
+      expect(event.data.merchant.toString()).to.equal(merchantPda.toString());
+      expect(event.data.discountKind.fixedAmount.amount.toNumber()).to.equal(1000);
+    });
+
+    it("should reject a zero fixed-amount discount", async () => {
+      await expectError(
+        createCouponBuilder(merchantAuthority, Keypair.generate(), {
+          discountKind: {
+            fixedAmount: { currency: [85, 83, 68], amount: new BN(0) },
+          },
+        }).rpc(),
+        "InvalidDiscount"
       );
+    });
 
-      try {
-        await program.methods
-          .createCoupon(
-            "Invalid Coupon",
-            "Invalid",
-            101, // Invalid: > 100
-            new BN(Date.now() / 1000 + 86400),
-            { other: {} },
-            1,
-            metadataUri
-          )
-          .accounts({
-            merchant: merchantPda,
-            couponData: invalidCouponPda,
-            nftMint: invalidMint.publicKey,
-            metadataAccount: invalidMetadata,
-            nftTokenAccount: invalidTokenAccount,
-            merchantAuthority: merchantAuthority.publicKey,
-            authority: merchantAuthority.publicKey,
-            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
-            tokenProgram: TOKEN_PROGRAM_ID,
-            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-            systemProgram: SystemProgram.programId,
-            rent: SYSVAR_RENT_PUBKEY,
-          })
-          .signers([merchantAuthority, invalidMint])
-          .rpc();
-        expect.fail("Should have thrown error for invalid discount");
-      } catch (error) {
-        expect(error.toString()).to.include("InvalidDiscountPercentage");
-      }
+    it("should fail with invalid discount percentage", async () => {
+      await expectError(
+        createCouponBuilder(merchantAuthority, Keypair.generate(), {
+          discountKind: { percentage: { pct: 101 } }, // Invalid: > 100
+        }).rpc(),
+        "InvalidDiscountPercentage"
+      );
     });
 
     it("should fail with past expiry date", async () => {
-      const invalidMint = Keypair.generate();
-      const [invalidCouponPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("coupon"), invalidMint.publicKey.toBuffer()],
-        program.programId
-      );
-      const [invalidMetadata] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("metadata"),
-          TOKEN_METADATA_PROGRAM_ID.toBuffer(),
-          invalidMint.publicKey.toBuffer(),
-        ],
-        TOKEN_METADATA_PROGRAM_ID
+      await expectError(
+        createCouponBuilder(merchantAuthority, Keypair.generate(), {
+          expiryDate: new BN(Math.floor(Date.now() / 1000) - 86400), // Past date
+        }).rpc(),
+        "InvalidExpiryDate"
       );
-      const invalidTokenAccount = getAssociatedTokenAddressSync(
-        invalidMint.publicKey,
-        merchantAuthority.publicKey
-      );
-
-      try {
-        await program.methods
-          .createCoupon(
-            "Expired Coupon",
-            "Already expired",
-            50,
-            new BN(Date.now() / 1000 - 86400), // Past date
-            { other: {} },
-            1,
-            metadataUri
-          )
-          .accounts({
-            merchant: merchantPda,
-            couponData: invalidCouponPda,
-            nftMint: invalidMint.publicKey,
-            metadataAccount: invalidMetadata,
-            nftTokenAccount: invalidTokenAccount,
-            merchantAuthority: merchantAuthority.publicKey,
-            authority: merchantAuthority.publicKey,
-            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
-            tokenProgram: TOKEN_PROGRAM_ID,
-            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-            systemProgram: SystemProgram.programId,
-            rent: SYSVAR_RENT_PUBKEY,
-          })
-          .signers([merchantAuthority, invalidMint])
-          .rpc();
-        expect.fail("Should have thrown error for past expiry date");
-      } catch (error) {
-        expect(error.toString()).to.include("InvalidExpiryDate");
-      }
     });
   });
 
   describe("update_coupon_status", () => {
     it("should update coupon active status", async () => {
-      // Deactivate coupon
-      await program.methods
-        .updateCouponStatus(false)
-        .accounts({
-          couponData: couponDataPda,
-          merchant: merchantPda,
-          merchantAuthority: merchantAuthority.publicKey,
-        })
-        .signers([merchantAuthority])
-        .rpc();
+      const coupon: Coupon = {
+        mint: nftMint.publicKey,
+        couponData: couponDataPda,
+        nftEscrow: nftEscrowPda,
+        merchant: merchantPda,
+      };
 
+      // Deactivate coupon
+      await couponStatusBuilder(coupon, merchantAuthority, false).rpc();
       let couponData = await program.account.couponData.fetch(couponDataPda);
       expect(couponData.isActive).to.be.false;
 
-      // Reactivate coupon
-      await program.methods
-        .updateCouponStatus(true)
-        .accounts({
-          couponData: couponDataPda,
-          merchant: merchantPda,
-          merchantAuthority: merchantAuthority.publicKey,
-        })
-        .signers([merchantAuthority])
-        .rpc();
-
+      // Reactivate coupon (never paid for, so no refund is owed)
+      await couponStatusBuilder(coupon, merchantAuthority, true).rpc();
       couponData = await program.account.couponData.fetch(couponDataPda);
       expect(couponData.isActive).to.be.true;
+      expect(couponData.refundOwed).to.be.false;
     });
 
     it("should fail when unauthorized user tries to update status", async () => {
-      const unauthorizedUser = Keypair.generate();
-      const airdrop = await provider.connection.requestAirdrop(
-        unauthorizedUser.publicKey,
-        2 * anchor.web3.LAMPORTS_PER_SOL
-      );
-      await provider.connection.confirmTransaction(airdrop);
-
-      try {
-        await program.methods
+      await expectError(
+        program.methods
           .updateCouponStatus(false)
-          .accounts({
-            couponData: couponDataPda,
+          .accountsPartial({
             merchant: merchantPda,
-            merchantAuthority: unauthorizedUser.publicKey,
+            couponData: couponDataPda,
+            revenueVault: null,
+            authority: merchantAuthority.publicKey,
+            merchantAuthority: userWallet.publicKey,
+            systemProgram: SystemProgram.programId,
           })
-          .signers([unauthorizedUser])
-          .rpc();
-        expect.fail("Should have thrown error for unauthorized access");
-      } catch (error) {
-        expect(error).to.exist;
-      }
+          .signers([userWallet])
+          .rpc(),
+        "UnauthorizedMerchant"
+      );
     });
   });
 
-  describe("redeem_coupon", () => {
+  describe("batch_update_coupon_status", () => {
+    let first: Coupon;
+    let second: Coupon;
+
     before(async () => {
-      // Transfer NFT to user for redemption test
-      userTokenAccount = getAssociatedTokenAddressSync(
-        nftMint.publicKey,
-        userWallet.publicKey
-      );
+      first = await createCoupon(merchantAuthority);
+      second = await createCoupon(merchantAuthority);
+    });
 
-      // Create user's token account and transfer NFT
-      const transferIx = await anchor.web3.SystemProgram.transfer({
-        fromPubkey: merchantAuthority.publicKey,
-        toPubkey: userWallet.publicKey,
-        lamports: 2 * anchor.web3.LAMPORTS_PER_SOL,
-      });
+    const batchBuilder = (isActive: boolean, coupons: PublicKey[]) =>
+      program.methods
+        .batchUpdateCouponStatus(isActive)
+        .accountsPartial({
+          merchant: merchantPda,
+          revenueVault: null,
+          authority: merchantAuthority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(
+          coupons.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }))
+        )
+        .signers([merchantAuthority]);
 
-      // Use anchor-spl to transfer the NFT
-      // For simplicity in testing, we'll create the associated token account
-      const createAtaIx = await (
-        await import("@solana/spl-token")
-      ).createAssociatedTokenAccountInstruction(
-        merchantAuthority.publicKey,
-        userTokenAccount,
-        userWallet.publicKey,
-        nftMint.publicKey
-      );
+    it("should pause and resume several coupons at once", async () => {
+      await batchBuilder(false, [first.couponData, second.couponData]).rpc();
+      for (const coupon of [first, second]) {
+        const data = await program.account.couponData.fetch(coupon.couponData);
+        expect(data.isActive).to.be.false;
+      }
 
-      const transferTokenIx = await (
-        await import("@solana/spl-token")
-      ).createTransferInstruction(
-        merchantTokenAccount,
-        userTokenAccount,
-        merchantAuthority.publicKey,
-        1
-      );
+      await batchBuilder(true, [first.couponData, second.couponData]).rpc();
+      for (const coupon of [first, second]) {
+        const data = await program.account.couponData.fetch(coupon.couponData);
+        expect(data.isActive).to.be.true;
+      }
+    });
+
+    it("should fail with an empty batch", async () => {
+      await expectError(batchBuilder(false, []).rpc(), "EmptyBatch");
+    });
+  });
 
-      const tx = new anchor.web3.Transaction()
-        .add(transferIx)
-        .add(createAtaIx)
-        .add(transferTokenIx);
+  describe("claim_coupon", () => {
+    it("should claim a free coupon into the user's wallet", async () => {
+      const coupon: Coupon = {
+        mint: nftMint.publicKey,
+        couponData: couponDataPda,
+        nftEscrow: nftEscrowPda,
+        merchant: merchantPda,
+      };
+      await claimBuilder(coupon, userWallet).rpc();
 
-      await provider.sendAndConfirm(tx, [merchantAuthority]);
+      userTokenAccount = ata(nftMint.publicKey, userWallet.publicKey);
+      expect(await tokenBalance(userTokenAccount)).to.equal(1);
+      expect(await tokenBalance(nftEscrowPda)).to.equal(0);
+    });
 
-      // Verify transfer
-      const userBalance = await provider.connection.getTokenAccountBalance(
-        userTokenAccount
-      );
-      expect(userBalance.value.uiAmount).to.equal(1);
+    it("should fail to claim a paid coupon", async () => {
+      const paid = await createCoupon(merchantAuthority, {
+        price: LAMPORTS_PER_SOL / 10,
+      });
+      await expectError(claimBuilder(paid, userWallet).rpc(), "NotFreeCoupon");
     });
+  });
 
-    it("should redeem coupon and burn NFT", async () => {
-      await program.methods
-        .redeemCoupon()
-        .accounts({
-          couponData: couponDataPda,
-          merchant: merchantPda,
-          nftMint: nftMint.publicKey,
-          nftTokenAccount: userTokenAccount,
-          user: userWallet.publicKey,
-          tokenProgram: TOKEN_PROGRAM_ID,
-        })
-        .signers([userWallet])
-        .rpc();
+  describe("redeem_coupon", () => {
+    it("should redeem coupon and burn NFT on the last use", async () => {
+      const coupon: Coupon = {
+        mint: nftMint.publicKey,
+        couponData: couponDataPda,
+        nftEscrow: nftEscrowPda,
+        merchant: merchantPda,
+      };
+      // Claiming used one of the two uses
+      await redeemBuilder(coupon, userWallet).rpc();
 
       // Verify coupon data updated
       const couponData = await program.account.couponData.fetch(couponDataPda);
       expect(couponData.redemptionsRemaining).to.equal(0);
 
       // Verify NFT was burned (token account should be closed or have 0 balance)
-      try {
-        const tokenBalance = await provider.connection.getTokenAccountBalance(
-          userTokenAccount
-        );
-        expect(tokenBalance.value.uiAmount).to.equal(0);
-      } catch (error) {
-        // Token account might be closed, which is also valid
-        expect(error).to.exist;
-      }
+      expect(await tokenBalance(userTokenAccount)).to.equal(0);
     });
 
     it("should fail to redeem already redeemed coupon", async () => {
-      // Try to redeem again
-      try {
-        await program.methods
-          .redeemCoupon()
-          .accounts({
-            couponData: couponDataPda,
-            merchant: merchantPda,
-            nftMint: nftMint.publicKey,
-            nftTokenAccount: userTokenAccount,
-            user: userWallet.publicKey,
-            tokenProgram: TOKEN_PROGRAM_ID,
-          })
-          .signers([userWallet])
-          .rpc();
-        expect.fail("Should have thrown error for fully redeemed coupon");
-      } catch (error) {
-        expect(error.toString()).to.include("CouponFullyRedeemed");
-      }
+      const coupon: Coupon = {
+        mint: nftMint.publicKey,
+        couponData: couponDataPda,
+        nftEscrow: nftEscrowPda,
+        merchant: merchantPda,
+      };
+      await expectError(redeemBuilder(coupon, userWallet).rpc());
     });
   });
 
   describe("multi-use coupon redemption", () => {
-    let multiUseMint: Keypair;
-    let multiUseCouponPda: PublicKey;
-    let multiUseMetadata: PublicKey;
-    let multiUseMerchantToken: PublicKey;
-    let multiUseUserToken: PublicKey;
-
     it("should create multi-use coupon and track redemptions", async () => {
-      multiUseMint = Keypair.generate();
-      [multiUseCouponPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("coupon"), multiUseMint.publicKey.toBuffer()],
-        program.programId
-      );
-      [multiUseMetadata] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("metadata"),
-          TOKEN_METADATA_PROGRAM_ID.toBuffer(),
-          multiUseMint.publicKey.toBuffer(),
-        ],
-        TOKEN_METADATA_PROGRAM_ID
-      );
-      multiUseMerchantToken = getAssociatedTokenAddressSync(
-        multiUseMint.publicKey,
-        merchantAuthority.publicKey
-      );
+      const coupon = await createCoupon(merchantAuthority, {
+        title: "Multi-Use Coffee Card",
+        description: "3 free coffees",
+        discountKind: { percentage: { pct: 100 } },
+        maxRedemptions: 4, // Claiming takes one
+      });
+      await claimBuilder(coupon, userWallet).rpc();
+      const userToken = ata(coupon.mint, userWallet.publicKey);
 
-      const expiryDate = new BN(Date.now() / 1000 + 86400 * 30);
-      const maxUses = 3;
+      // Redeem twice (should not burn yet)
+      await redeemBuilder(coupon, userWallet).rpc();
+      let couponData = await program.account.couponData.fetch(coupon.couponData);
+      expect(couponData.redemptionsRemaining).to.equal(2);
+      expect(await tokenBalance(userToken)).to.equal(1);
 
-      await program.methods
-        .createCoupon(
-          "Multi-Use Coffee Card",
-          "3 free coffees",
-          100,
-          expiryDate,
-          { foodAndBeverage: {} },
-          maxUses,
-          metadataUri
-        )
-        .accounts({
-          merchant: merchantPda,
-          couponData: multiUseCouponPda,
-          nftMint: multiUseMint.publicKey,
-          metadataAccount: multiUseMetadata,
-          nftTokenAccount: multiUseMerchantToken,
-          merchantAuthority: merchantAuthority.publicKey,
-          authority: merchantAuthority.publicKey,
-          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      await redeemBuilder(coupon, userWallet).rpc();
+      couponData = await program.account.couponData.fetch(coupon.couponData);
+      expect(couponData.redemptionsRemaining).to.equal(1);
+      expect(await tokenBalance(userToken)).to.equal(1);
+
+      // Final redemption should burn NFT
+      await redeemBuilder(coupon, userWallet).rpc();
+      couponData = await program.account.couponData.fetch(coupon.couponData);
+      expect(couponData.redemptionsRemaining).to.equal(0);
+      expect(await tokenBalance(userToken)).to.equal(0);
+    });
+  });
+
+  describe("redeem_amount (stored value)", () => {
+    let giftCard: Coupon;
+
+    const redeemAmountBuilder = (coupon: Coupon, amount: number) =>
+      program.methods
+        .redeemAmount(new BN(amount))
+        .accountsPartial({
+          couponData: coupon.couponData,
+          merchant: coupon.merchant,
+          nftMint: coupon.mint,
+          nftTokenAccount: ata(coupon.mint, userWallet.publicKey),
+          user: userWallet.publicKey,
+          redeemerRecord: redeemerRecordFor(coupon.merchant, userWallet.publicKey),
           tokenProgram: TOKEN_PROGRAM_ID,
-          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
-          rent: SYSVAR_RENT_PUBKEY,
         })
-        .signers([merchantAuthority, multiUseMint])
-        .rpc();
+        .signers([userWallet]);
 
-      // Transfer to user
-      multiUseUserToken = getAssociatedTokenAddressSync(
-        multiUseMint.publicKey,
-        userWallet.publicKey
-      );
+    before(async () => {
+      giftCard = await createCoupon(merchantAuthority, {
+        title: "$50 Gift Card",
+        discountKind: {
+          storedValue: { currency: [85, 83, 68], amount: new BN(5000) },
+        },
+      });
+      await claimBuilder(giftCard, userWallet).rpc();
+    });
 
-      const createAtaIx = await (
-        await import("@solana/spl-token")
-      ).createAssociatedTokenAccountInstruction(
-        merchantAuthority.publicKey,
-        multiUseUserToken,
-        userWallet.publicKey,
-        multiUseMint.publicKey
+    it("should spend part of the balance", async () => {
+      await redeemAmountBuilder(giftCard, 2000).rpc();
+      const data = await program.account.couponData.fetch(giftCard.couponData);
+      expect(data.balance.toNumber()).to.equal(3000);
+      expect(
+        await tokenBalance(ata(giftCard.mint, userWallet.publicKey))
+      ).to.equal(1);
+    });
+
+    it("should fail to spend more than the balance", async () => {
+      await expectError(
+        redeemAmountBuilder(giftCard, 5000).rpc(),
+        "InsufficientBalance"
       );
+    });
 
-      const transferTokenIx = await (
-        await import("@solana/spl-token")
-      ).createTransferInstruction(
-        multiUseMerchantToken,
-        multiUseUserToken,
-        merchantAuthority.publicKey,
-        1
+    it("should refuse redeem_coupon on a stored-value coupon", async () => {
+      await expectError(
+        redeemBuilder(giftCard, userWallet).rpc(),
+        "UseRedeemAmount"
       );
+    });
+  });
 
-      const tx = new anchor.web3.Transaction()
-        .add(createAtaIx)
-        .add(transferTokenIx);
-      await provider.sendAndConfirm(tx, [merchantAuthority]);
+  describe("set_coupon_allowlist", () => {
+    let vipCoupon: Coupon;
+    // Single-leaf tree: the root is the leaf and the proof is empty
+    const leafFor = (wallet: PublicKey, quota: number) => {
+      const quotaLe = Buffer.alloc(2);
+      quotaLe.writeUInt16LE(quota);
+      return [
+        ...createHash("sha256")
+          .update(Buffer.concat([wallet.toBuffer(), quotaLe]))
+          .digest(),
+      ];
+    };
 
-      // Redeem twice (should not burn yet)
+    before(async () => {
+      vipCoupon = await createCoupon(merchantAuthority, { title: "VIP Drop" });
+    });
+
+    it("should restrict claims to allowlisted wallets", async () => {
       await program.methods
-        .redeemCoupon()
-        .accounts({
-          couponData: multiUseCouponPda,
-          merchant: merchantPda,
-          nftMint: multiUseMint.publicKey,
-          nftTokenAccount: multiUseUserToken,
-          user: userWallet.publicKey,
-          tokenProgram: TOKEN_PROGRAM_ID,
-        })
-        .signers([userWallet])
+        .setCouponAllowlist(leafFor(userWallet.publicKey, 0))
+        .accountsPartial(couponSetterAccounts(vipCoupon, merchantAuthority))
+        .signers([merchantAuthority])
         .rpc();
 
-      let couponData = await program.account.couponData.fetch(multiUseCouponPda);
-      expect(couponData.redemptionsRemaining).to.equal(2);
-
-      // NFT should still exist
-      let tokenBalance = await provider.connection.getTokenAccountBalance(
-        multiUseUserToken
-      );
-      expect(tokenBalance.value.uiAmount).to.equal(1);
-
-      // Redeem second time
       await program.methods
-        .redeemCoupon()
-        .accounts({
-          couponData: multiUseCouponPda,
-          merchant: merchantPda,
-          nftMint: multiUseMint.publicKey,
-          nftTokenAccount: multiUseUserToken,
+        .claimCoupon({ proof: [], quota: 0 })
+        .accountsPartial({
+          couponData: vipCoupon.couponData,
+          merchant: vipCoupon.merchant,
+          nftEscrow: vipCoupon.nftEscrow,
+          nftMint: vipCoupon.mint,
+          userTokenAccount: ata(vipCoupon.mint, userWallet.publicKey),
           user: userWallet.publicKey,
+          allowlistReceipt: null,
+          redeemerRecord: redeemerRecordFor(vipCoupon.merchant, userWallet.publicKey),
           tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
         })
         .signers([userWallet])
         .rpc();
+      expect(
+        await tokenBalance(ata(vipCoupon.mint, userWallet.publicKey))
+      ).to.equal(1);
+    });
 
-      couponData = await program.account.couponData.fetch(multiUseCouponPda);
-      expect(couponData.redemptionsRemaining).to.equal(1);
-
-      // NFT should still exist
-      tokenBalance = await provider.connection.getTokenAccountBalance(
-        multiUseUserToken
-      );
-      expect(tokenBalance.value.uiAmount).to.equal(1);
+    it("should reject wallets without a valid proof", async () => {
+      const other = await createCoupon(merchantAuthority, { title: "VIP Drop 2" });
+      await program.methods
+        .setCouponAllowlist(leafFor(userWallet.publicKey, 0))
+        .accountsPartial(couponSetterAccounts(other, merchantAuthority))
+        .signers([merchantAuthority])
+        .rpc();
+
+      await expectError(claimBuilder(other, otherWallet).rpc(), "NotOnAllowlist");
+    });
+  });
+
+  describe("set_coupon_gate", () => {
+    let gatedCoupon: Coupon;
+    let gateMint: PublicKey;
+
+    before(async () => {
+      gatedCoupon = await createCoupon(merchantAuthority, { title: "Holders Only" });
+      gateMint = await createMint(
+        connection,
+        merchantAuthority,
+        merchantAuthority.publicKey,
+        null,
+        0
+      );
+      await gateBuilder({ tokenBalance: {} }, gateMint, 1).rpc();
+    });
+
+    const gateBuilder = (gateKind: object, mint: PublicKey, minAmount: number) =>
+      program.methods
+        .setCouponGate(gateKind as any, mint, new BN(minAmount))
+        .accountsPartial({
+          ...couponSetterAccounts(gatedCoupon, merchantAuthority),
+          nftEscrow: gatedCoupon.nftEscrow,
+        })
+        .signers([merchantAuthority]);
+
+    it("should reject a gate without a mint or a mint without a gate", async () => {
+      await expectError(
+        gateBuilder({ tokenBalance: {} }, PublicKey.default, 1).rpc(),
+        "InvalidGateConfig"
+      );
+      await expectError(gateBuilder({ none: {} }, gateMint, 0).rpc(), "InvalidGateConfig");
+    });
+
+    it("should reject claims without the gate token account", async () => {
+      await expectError(
+        claimBuilder(gatedCoupon, otherWallet).rpc(),
+        "MissingGateAccounts"
+      );
+    });
+
+    it("should allow holders of the gate token to claim", async () => {
+      const holding = await getOrCreateAssociatedTokenAccount(
+        connection,
+        merchantAuthority,
+        gateMint,
+        otherWallet.publicKey
+      );
+      await mintTo(
+        connection,
+        merchantAuthority,
+        gateMint,
+        holding.address,
+        merchantAuthority,
+        1
+      );
+
+      await claimBuilder(gatedCoupon, otherWallet)
+        .remainingAccounts([
+          { pubkey: holding.address, isSigner: false, isWritable: false },
+        ])
+        .rpc();
+      expect(
+        await tokenBalance(ata(gatedCoupon.mint, otherWallet.publicKey))
+      ).to.equal(1);
+    });
+
+    it("should lock the gate once the coupon has left escrow", async () => {
+      await expectError(gateBuilder({ none: {} }, PublicKey.default, 0).rpc(), "GateLocked");
+    });
+  });
+
+  describe("set_coupon_windows", () => {
+    let scheduled: Coupon;
+
+    before(async () => {
+      scheduled = await createCoupon(merchantAuthority, { title: "Launch Day" });
+    });
+
+    const windowsBuilder = (saleStart: number, saleEnd: number) =>
+      program.methods
+        .setCouponWindows(new BN(saleStart), new BN(saleEnd), new BN(0), new BN(0))
+        .accountsPartial(couponSetterAccounts(scheduled, merchantAuthority))
+        .signers([merchantAuthority]);
+
+    it("should block claims before the sale starts", async () => {
+      const saleStart = (await chainNow()) + 86400;
+      await windowsBuilder(saleStart, 0).rpc();
+      const data = await program.account.couponData.fetch(scheduled.couponData);
+      expect(data.saleStart.toNumber()).to.equal(saleStart);
+
+      await expectError(claimBuilder(scheduled, userWallet).rpc(), "SaleNotStarted");
+    });
+
+    it("should reject a window that ends before it starts", async () => {
+      const now = await chainNow();
+      await expectError(
+        windowsBuilder(now + 7200, now + 3600).rpc(),
+        "InvalidTimeWindow"
+      );
+    });
+  });
+
+  describe("set_redemption_schedule", () => {
+    let happyHour: Coupon;
+
+    before(async () => {
+      happyHour = await createCoupon(merchantAuthority, { title: "Happy Hour" });
+    });
+
+    const scheduleBuilder = (schedule: object | null) =>
+      program.methods
+        .setRedemptionSchedule(schedule as any)
+        .accountsPartial(couponSetterAccounts(happyHour, merchantAuthority))
+        .signers([merchantAuthority]);
+
+    it("should set and clear a weekly schedule", async () => {
+      await scheduleBuilder({
+        weekdayMask: 0b0011111, // Mon-Fri
+        startMinute: 15 * 60,
+        endMinute: 18 * 60,
+        utcOffsetMinutes: -300,
+      }).rpc();
+      let data = await program.account.couponData.fetch(happyHour.couponData);
+      expect(data.redemptionSchedule.weekdayMask).to.equal(0b0011111);
+
+      await scheduleBuilder(null).rpc();
+      data = await program.account.couponData.fetch(happyHour.couponData);
+      expect(data.redemptionSchedule).to.be.null;
+    });
+
+    it("should reject offsets below UTC-12:00", async () => {
+      await expectError(
+        scheduleBuilder({
+          weekdayMask: 0b1111111,
+          startMinute: 0,
+          endMinute: 60,
+          utcOffsetMinutes: -13 * 60,
+        }).rpc(),
+        "InvalidRedemptionSchedule"
+      );
+    });
+  });
+
+  describe("set_redemption_cooldown", () => {
+    let punchCard: Coupon;
+
+    before(async () => {
+      punchCard = await createCoupon(merchantAuthority, {
+        title: "Coffee Punch Card",
+        maxRedemptions: 5,
+      });
+      await claimBuilder(punchCard, userWallet).rpc();
+    });
+
+    const cooldownBuilder = (seconds: number) =>
+      program.methods
+        .setRedemptionCooldown(new BN(seconds))
+        .accountsPartial(couponSetterAccounts(punchCard, merchantAuthority))
+        .signers([merchantAuthority]);
+
+    it("should block back-to-back redemptions", async () => {
+      await cooldownBuilder(3600).rpc();
+      await redeemBuilder(punchCard, userWallet).rpc();
+      await expectError(
+        redeemBuilder(punchCard, userWallet).rpc(),
+        "RedemptionCooldownActive"
+      );
+    });
+
+    it("should reject a negative cooldown", async () => {
+      await expectError(cooldownBuilder(-1).rpc(), "InvalidCooldown");
+    });
+  });
+
+  describe("migrate_merchant / migrate_coupon", () => {
+    // Accounts created by this build are already current; older layouts
+    // can't be produced from the client, so only the guard is exercised here
+    it("should refuse to migrate a current merchant", async () => {
+      await expectError(
+        program.methods
+          .migrateMerchant()
+          .accountsPartial({
+            merchant: merchantPda,
+            payer: merchantAuthority.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([merchantAuthority])
+          .rpc(),
+        "AlreadyMigrated"
+      );
+    });
+
+    it("should refuse to migrate a current coupon", async () => {
+      await expectError(
+        program.methods
+          .migrateCoupon()
+          .accountsPartial({
+            couponData: couponDataPda,
+            payer: merchantAuthority.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([merchantAuthority])
+          .rpc(),
+        "AlreadyMigrated"
+      );
+    });
+  });
+
+  describe("update_merchant_profile", () => {
+    it("should update the merchant profile", async () => {
+      await program.methods
+        .updateMerchantProfile(
+          "Test Coffee Roasters",
+          "https://example.com/logo.png",
+          "https://example.com",
+          { foodAndBeverage: {} } as any
+        )
+        .accountsPartial({
+          merchant: merchantPda,
+          authority: merchantAuthority.publicKey,
+        })
+        .signers([merchantAuthority])
+        .rpc();
+      const merchant = await program.account.merchant.fetch(merchantPda);
+      expect(merchant.businessName).to.equal("Test Coffee Roasters");
+      expect(merchant.website).to.equal("https://example.com");
+    });
+
+    it("should reject an over-long business name", async () => {
+      await expectError(
+        program.methods
+          .updateMerchantProfile("x".repeat(101), "", "", { other: {} } as any)
+          .accountsPartial({
+            merchant: merchantPda,
+            authority: merchantAuthority.publicKey,
+          })
+          .signers([merchantAuthority])
+          .rpc(),
+        "BusinessNameTooLong"
+      );
+    });
+  });
+
+  describe("merchant authority rotation", () => {
+    let oldAuthority: Keypair;
+    let newAuthority: Keypair;
+    let rotatingMerchant: PublicKey;
+
+    before(async () => {
+      oldAuthority = await newFundedWallet(5);
+      newAuthority = await newFundedWallet(1);
+      rotatingMerchant = await initializeMerchant(oldAuthority, "Rotating Shop");
+    });
+
+    it("should reject accept_authority from a wallet that wasn't proposed", async () => {
+      await program.methods
+        .proposeAuthority(newAuthority.publicKey)
+        .accountsPartial({
+          merchant: rotatingMerchant,
+          authority: oldAuthority.publicKey,
+        })
+        .signers([oldAuthority])
+        .rpc();
+
+      await expectError(
+        program.methods
+          .acceptAuthority()
+          .accountsPartial({
+            merchant: rotatingMerchant,
+            newAuthority: userWallet.publicKey,
+          })
+          .signers([userWallet])
+          .rpc(),
+        "NotPendingAuthority"
+      );
+    });
+
+    it("should rotate authority without moving the merchant PDA", async () => {
+      await program.methods
+        .acceptAuthority()
+        .accountsPartial({
+          merchant: rotatingMerchant,
+          newAuthority: newAuthority.publicKey,
+        })
+        .signers([newAuthority])
+        .rpc();
+      const merchant = await program.account.merchant.fetch(rotatingMerchant);
+      expect(merchant.authority.toString()).to.equal(
+        newAuthority.publicKey.toString()
+      );
+      expect(merchant.seedAuthority.toString()).to.equal(
+        oldAuthority.publicKey.toString()
+      );
+      expect(merchant.pendingAuthority).to.be.null;
+    });
+
+    it("should refuse propose_authority from the old authority", async () => {
+      await expectError(
+        program.methods
+          .proposeAuthority(oldAuthority.publicKey)
+          .accountsPartial({
+            merchant: rotatingMerchant,
+            authority: oldAuthority.publicKey,
+          })
+          .signers([oldAuthority])
+          .rpc(),
+        "UnauthorizedMerchant"
+      );
+    });
+  });
+
+  describe("verify_merchant / revoke_merchant", () => {
+    const adminBuilder = (method: "verifyMerchant" | "revokeMerchant") =>
+      program.methods[method]().accountsPartial({
+        platformConfig: platformConfigPda,
+        merchant: merchantPda,
+        admin: provider.wallet.publicKey,
+      });
+
+    it("should verify and revoke a merchant (admin)", async () => {
+      await adminBuilder("verifyMerchant").rpc();
+      let merchant = await program.account.merchant.fetch(merchantPda);
+      expect(merchant.verificationStatus).to.deep.equal({ verified: {} });
+
+      await adminBuilder("revokeMerchant").rpc();
+      merchant = await program.account.merchant.fetch(merchantPda);
+      expect(merchant.verificationStatus).to.deep.equal({ revoked: {} });
+    });
+
+    it("should refuse verification from a non-admin", async () => {
+      await expectError(
+        program.methods
+          .verifyMerchant()
+          .accountsPartial({
+            platformConfig: platformConfigPda,
+            merchant: merchantPda,
+            admin: merchantAuthority.publicKey,
+          })
+          .signers([merchantAuthority])
+          .rpc(),
+        "UnauthorizedAdmin"
+      );
+    });
+  });
+
+  describe("set_merchant_suspension", () => {
+    const suspensionBuilder = (isSuspended: boolean) =>
+      program.methods.setMerchantSuspension(isSuspended).accountsPartial({
+        platformConfig: platformConfigPda,
+        merchant: merchantPda,
+        admin: provider.wallet.publicKey,
+      });
+
+    it("should freeze trading while a merchant is suspended", async () => {
+      const coupon = await createCoupon(merchantAuthority, { title: "Frozen" });
+      await suspensionBuilder(true).rpc();
+      try {
+        await expectError(
+          claimBuilder(coupon, userWallet).rpc(),
+          "MerchantSuspended"
+        );
+      } finally {
+        await suspensionBuilder(false).rpc();
+      }
+      await claimBuilder(coupon, userWallet).rpc();
+    });
+
+    it("should refuse suspension from a non-admin", async () => {
+      await expectError(
+        program.methods
+          .setMerchantSuspension(true)
+          .accountsPartial({
+            platformConfig: platformConfigPda,
+            merchant: merchantPda,
+            admin: userWallet.publicKey,
+          })
+          .signers([userWallet])
+          .rpc(),
+        "UnauthorizedAdmin"
+      );
+    });
+  });
+
+  describe("close_merchant", () => {
+    it("should refuse to close a merchant with open coupons", async () => {
+      await expectError(
+        program.methods
+          .closeMerchant()
+          .accountsPartial({
+            merchant: merchantPda,
+            revenueVault: revenueVaultFor(merchantPda),
+            authority: merchantAuthority.publicKey,
+          })
+          .signers([merchantAuthority])
+          .rpc(),
+        "MerchantHasOpenCoupons"
+      );
+    });
+
+    it("should close a merchant without open coupons", async () => {
+      const leavingAuthority = await newFundedWallet(1);
+      const leavingMerchant = await initializeMerchant(leavingAuthority, "Closing Down");
+
+      await program.methods
+        .closeMerchant()
+        .accountsPartial({
+          merchant: leavingMerchant,
+          revenueVault: revenueVaultFor(leavingMerchant),
+          authority: leavingAuthority.publicKey,
+        })
+        .signers([leavingAuthority])
+        .rpc();
+      expect(await connection.getAccountInfo(leavingMerchant)).to.be.null;
+    });
+
+    it("should close the merchant's revenue vault with it", async () => {
+      const { authority, merchant, revenueVault } = await initializeDeferredMerchant(
+        "Deferred Closing Down"
+      );
+      const vaultLamports = await balance(revenueVault);
+      const merchantLamports = await balance(merchant);
+      const authorityBefore = await balance(authority.publicKey);
+
+      await program.methods
+        .closeMerchant()
+        .accountsPartial({
+          merchant,
+          revenueVault,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+      expect(await connection.getAccountInfo(merchant)).to.be.null;
+      expect(await connection.getAccountInfo(revenueVault)).to.be.null;
+      expect(await balance(authority.publicKey)).to.be.closeTo(
+        authorityBefore + vaultLamports + merchantLamports,
+        TX_FEE_SLACK
+      );
+    });
+  });
+
+  describe("close_coupon", () => {
+    const closeBuilder = (coupon: Coupon) =>
+      program.methods
+        .closeCoupon()
+        .accountsPartial({
+          merchant: coupon.merchant,
+          couponData: coupon.couponData,
+          nftMint: coupon.mint,
+          nftEscrow: coupon.nftEscrow,
+          auction: auctionFor(coupon.mint),
+          authority: merchantAuthority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([merchantAuthority]);
+
+    it("should burn an unsold coupon and close its accounts", async () => {
+      const unsold = await createCoupon(merchantAuthority, { title: "Unsold" });
+      const before = await program.account.merchant.fetch(merchantPda);
+
+      await closeBuilder(unsold).rpc();
+      expect(await connection.getAccountInfo(unsold.couponData)).to.be.null;
+      expect(await connection.getAccountInfo(unsold.nftEscrow)).to.be.null;
+      const after = await program.account.merchant.fetch(merchantPda);
+      expect(after.openCoupons.toNumber()).to.equal(
+        before.openCoupons.toNumber() - 1
+      );
+    });
+
+    it("should refuse to close a live coupon someone holds", async () => {
+      const live = await createCoupon(merchantAuthority, { title: "Live" });
+      await claimBuilder(live, userWallet).rpc();
+      await expectError(closeBuilder(live).rpc(), "CouponStillLive");
+    });
+  });
+
+  describe("merchant sales and redemption stats", () => {
+    const price = LAMPORTS_PER_SOL / 10;
+    let statsAuthority: Keypair;
+    let statsMerchant: PublicKey;
+
+    before(async () => {
+      statsAuthority = await newFundedWallet(5);
+      statsMerchant = await initializeMerchant(statsAuthority, "Stats Cafe");
+    });
+
+    it("should count claims, sales, revenue, fees and redemptions", async () => {
+      const free = await createCoupon(statsAuthority);
+      await claimBuilder(free, userWallet).rpc();
+      const paid = await createCoupon(statsAuthority, { price });
+      await purchaseBuilder(paid, otherWallet, statsAuthority.publicKey, price).rpc();
+      await redeemBuilder(paid, otherWallet).rpc();
+
+      const merchant = await program.account.merchant.fetch(statsMerchant);
+      expect(merchant.totalClaims.toNumber()).to.equal(1);
+      expect(merchant.totalSales.toNumber()).to.equal(1);
+      expect(merchant.grossRevenueLamports.toNumber()).to.equal(price);
+      expect(merchant.feesPaid.toNumber()).to.equal(platformFee(price));
+      expect(merchant.totalRedemptions.toNumber()).to.equal(1);
+      expect(merchant.uniqueRedeemers.toNumber()).to.equal(1);
+    });
+
+    it("should count each redeeming wallet once", async () => {
+      const reusable = await createCoupon(statsAuthority, { maxRedemptions: 3 });
+      await claimBuilder(reusable, otherWallet).rpc();
+      await redeemBuilder(reusable, otherWallet).rpc();
+
+      let merchant = await program.account.merchant.fetch(statsMerchant);
+      expect(merchant.totalRedemptions.toNumber()).to.equal(2);
+      expect(merchant.uniqueRedeemers.toNumber()).to.equal(1);
+
+      const record = await program.account.redeemerRecord.fetch(
+        redeemerRecordFor(statsMerchant, otherWallet.publicKey)
+      );
+      expect(record.redemptions.toNumber()).to.equal(2);
+
+      const second = await createCoupon(statsAuthority, { maxRedemptions: 3 });
+      await claimBuilder(second, userWallet).rpc();
+      await redeemBuilder(second, userWallet).rpc();
+
+      merchant = await program.account.merchant.fetch(statsMerchant);
+      expect(merchant.totalRedemptions.toNumber()).to.equal(3);
+      expect(merchant.uniqueRedeemers.toNumber()).to.equal(2);
+    });
+
+    it("should leave the stats untouched by a failed redemption", async () => {
+      const spent = await createCoupon(statsAuthority, { maxRedemptions: 1 });
+      await claimBuilder(spent, userWallet).rpc();
+      // Claiming used the only use
+      await expectError(redeemBuilder(spent, userWallet).rpc(), "CouponFullyRedeemed");
+
+      const merchant = await program.account.merchant.fetch(statsMerchant);
+      expect(merchant.totalClaims.toNumber()).to.equal(4);
+      expect(merchant.totalRedemptions.toNumber()).to.equal(3);
+      expect(merchant.uniqueRedeemers.toNumber()).to.equal(2);
+    });
+  });
+
+  describe("purchase_coupon", () => {
+    const price = LAMPORTS_PER_SOL / 10;
+    let paidCoupon: Coupon;
+
+    before(async () => {
+      paidCoupon = await createCoupon(merchantAuthority, {
+        title: "Paid Coffee",
+        price,
+      });
+    });
+
+    it("should fail when the price exceeds max_price", async () => {
+      await expectError(
+        purchaseBuilder(paidCoupon, userWallet, merchantAuthority.publicKey, price - 1).rpc(),
+        "PriceExceedsMaximum"
+      );
+    });
+
+    it("should split the price 97.5% merchant / 2.5% platform", async () => {
+      const merchantBefore = await balance(merchantAuthority.publicKey);
+      const feeBefore = await balance(feeWallet.publicKey);
+
+      await purchaseBuilder(paidCoupon, userWallet, merchantAuthority.publicKey, price).rpc();
+
+      expect(await balance(merchantAuthority.publicKey)).to.equal(
+        merchantBefore + price - platformFee(price)
+      );
+      expect(await balance(feeWallet.publicKey)).to.equal(
+        feeBefore + platformFee(price)
+      );
+      expect(
+        await tokenBalance(ata(paidCoupon.mint, userWallet.publicKey))
+      ).to.equal(1);
+
+      const data = await program.account.couponData.fetch(paidCoupon.couponData);
+      expect(data.purchaser.toString()).to.equal(userWallet.publicKey.toString());
+    });
+  });
+
+  describe("set_price_curve", () => {
+    const startPrice = LAMPORTS_PER_SOL / 5;
+    const endPrice = LAMPORTS_PER_SOL / 20;
+    let dutchCoupon: Coupon;
+
+    before(async () => {
+      dutchCoupon = await createCoupon(merchantAuthority, {
+        title: "Clearance",
+        price: startPrice,
+      });
+    });
+
+    const curveBuilder = (curve: object | null) =>
+      program.methods
+        .setPriceCurve(curve as any)
+        .accountsPartial(couponSetterAccounts(dutchCoupon, merchantAuthority))
+        .signers([merchantAuthority]);
+
+    it("should reject a curve starting before the epoch", async () => {
+      await expectError(
+        curveBuilder({
+          startPrice: new BN(startPrice),
+          endPrice: new BN(endPrice),
+          startTime: new BN(-1),
+          endTime: new BN(1),
+          stepSeconds: new BN(0),
+        }).rpc(),
+        "InvalidPriceCurve"
+      );
+    });
+
+    it("should charge the floor price once the curve has ended", async () => {
+      const now = await chainNow();
+      await curveBuilder({
+        startPrice: new BN(startPrice),
+        endPrice: new BN(endPrice),
+        startTime: new BN(now - 7200),
+        endTime: new BN(now - 3600),
+        stepSeconds: new BN(0),
+      }).rpc();
+
+      const feeBefore = await balance(feeWallet.publicKey);
+      await purchaseBuilder(
+        dutchCoupon,
+        otherWallet,
+        merchantAuthority.publicKey,
+        endPrice
+      ).rpc();
+      expect(await balance(feeWallet.publicKey)).to.equal(
+        feeBefore + platformFee(endPrice)
+      );
+    });
+  });
+
+  describe("transfer_coupon", () => {
+    let coupon: Coupon;
+    const price = LAMPORTS_PER_SOL / 10;
+
+    before(async () => {
+      coupon = await createCoupon(merchantAuthority, { title: "P2P" });
+      await claimBuilder(coupon, userWallet).rpc();
+    });
+
+    it("should fail with a zero price", async () => {
+      await expectError(
+        transferBuilder(coupon, userWallet, otherWallet, 0).rpc(),
+        "InvalidPrice"
+      );
+    });
+
+    it("should refuse to trade a deactivated coupon", async () => {
+      await couponStatusBuilder(coupon, merchantAuthority, false).rpc();
+      try {
+        await expectError(
+          transferBuilder(coupon, userWallet, otherWallet, price).rpc(),
+          "CouponInactive"
+        );
+        await expectError(listBuilder(coupon, userWallet, price).rpc(), "CouponInactive");
+      } finally {
+        await couponStatusBuilder(coupon, merchantAuthority, true).rpc();
+      }
+    });
+
+    it("should swap the NFT for SOL with the platform fee", async () => {
+      const feeBefore = await balance(feeWallet.publicKey);
+      const signature = await transferBuilder(coupon, userWallet, otherWallet, price).rpc({
+        commitment: "confirmed",
+      });
+      expect(await balance(feeWallet.publicKey)).to.equal(
+        feeBefore + platformFee(price)
+      );
+      expect(await tokenBalance(ata(coupon.mint, otherWallet.publicKey))).to.equal(1);
+
+      // Claiming took one of the two uses: the buyer is told one is left
+      const event = (await eventsOf(signature)).find(
+        (e) => e.name === "couponTradedEvent"
+      );
+      expect(event, "CouponTradedEvent").to.exist;
+      expect(event.data.usesLeft).to.equal(maxRedemptions - 1);
+    });
+  });
+
+  describe("resale listings", () => {
+    const price = LAMPORTS_PER_SOL / 10;
+    let coupon: Coupon;
+
+    before(async () => {
+      coupon = await createCoupon(merchantAuthority, { title: "Resale" });
+      await claimBuilder(coupon, userWallet).rpc();
+    });
+
+    it("should list the NFT into the resale escrow", async () => {
+      await listBuilder(coupon, userWallet, price).rpc();
+      const listing = await program.account.resaleListing.fetch(
+        listingFor(coupon.mint, userWallet.publicKey)
+      );
+      expect(listing.price.toNumber()).to.equal(price);
+      expect(
+        await tokenBalance(resaleEscrowFor(coupon.mint, userWallet.publicKey))
+      ).to.equal(1);
+    });
+
+    it("should update the listing price (seller only)", async () => {
+      await program.methods
+        .updateListingPrice(new BN(price * 2))
+        .accountsPartial({
+          listing: listingFor(coupon.mint, userWallet.publicKey),
+          seller: userWallet.publicKey,
+        })
+        .signers([userWallet])
+        .rpc();
+      const listing = await program.account.resaleListing.fetch(
+        listingFor(coupon.mint, userWallet.publicKey)
+      );
+      expect(listing.price.toNumber()).to.equal(price * 2);
+
+      await expectError(
+        program.methods
+          .updateListingPrice(new BN(1))
+          .accountsPartial({
+            listing: listingFor(coupon.mint, userWallet.publicKey),
+            seller: otherWallet.publicKey,
+          })
+          .signers([otherWallet])
+          .rpc()
+      );
+    });
+
+    it("should fail when the listing price exceeds expected_price", async () => {
+      await expectError(
+        purchaseFromResaleBuilder(coupon, userWallet.publicKey, otherWallet, price).rpc(),
+        "PriceExceedsMaximum"
+      );
+    });
+
+    it("should sell from the escrow with the platform fee", async () => {
+      const sellerBefore = await balance(userWallet.publicKey);
+      const feeBefore = await balance(feeWallet.publicKey);
+
+      await purchaseFromResaleBuilder(
+        coupon,
+        userWallet.publicKey,
+        otherWallet,
+        price * 2
+      ).rpc();
+
+      expect(await balance(feeWallet.publicKey)).to.equal(
+        feeBefore + platformFee(price * 2)
+      );
+      // Seller also gets the listing rent back
+      expect(await balance(userWallet.publicKey)).to.be.at.least(
+        sellerBefore + price * 2 - platformFee(price * 2)
+      );
+      expect(await tokenBalance(ata(coupon.mint, otherWallet.publicKey))).to.equal(1);
+      expect(
+        await connection.getAccountInfo(listingFor(coupon.mint, userWallet.publicKey))
+      ).to.be.null;
+    });
+  });
+
+  describe("cancel_listing", () => {
+    let coupon: Coupon;
+
+    const cancelBuilder = (seller: Keypair, withListing = true) =>
+      program.methods
+        .cancelListing()
+        .accountsPartial({
+          nftMint: coupon.mint,
+          listing: withListing ? listingFor(coupon.mint, seller.publicKey) : null,
+          resaleEscrow: resaleEscrowFor(coupon.mint, seller.publicKey),
+          sellerTokenAccount: ata(coupon.mint, seller.publicKey),
+          seller: seller.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller]);
+
+    before(async () => {
+      coupon = await createCoupon(merchantAuthority, { title: "Delist" });
+      await claimBuilder(coupon, userWallet).rpc();
+      await listBuilder(coupon, userWallet, LAMPORTS_PER_SOL).rpc();
+    });
+
+    it("should refuse anyone but the seller", async () => {
+      await expectError(
+        program.methods
+          .cancelListing()
+          .accountsPartial({
+            nftMint: coupon.mint,
+            listing: listingFor(coupon.mint, userWallet.publicKey),
+            resaleEscrow: resaleEscrowFor(coupon.mint, userWallet.publicKey),
+            sellerTokenAccount: ata(coupon.mint, otherWallet.publicKey),
+            seller: otherWallet.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([otherWallet])
+          .rpc()
+      );
+    });
+
+    it("should return the NFT and close the listing before expiry", async () => {
+      await cancelBuilder(userWallet).rpc();
+      expect(await tokenBalance(ata(coupon.mint, userWallet.publicKey))).to.equal(1);
+      expect(
+        await connection.getAccountInfo(resaleEscrowFor(coupon.mint, userWallet.publicKey))
+      ).to.be.null;
+      expect(
+        await connection.getAccountInfo(listingFor(coupon.mint, userWallet.publicKey))
+      ).to.be.null;
+    });
+
+    it("should recover an escrow without a listing account", async () => {
+      // Leave the listing out, as for escrows created before ResaleListing existed
+      await listBuilder(coupon, userWallet, LAMPORTS_PER_SOL).rpc();
+      await cancelBuilder(userWallet, false).rpc();
+      expect(await tokenBalance(ata(coupon.mint, userWallet.publicKey))).to.equal(1);
+      expect(
+        await connection.getAccountInfo(resaleEscrowFor(coupon.mint, userWallet.publicKey))
+      ).to.be.null;
+    });
+  });
+
+  describe("crank_expired_listing", () => {
+    let coupon: Coupon;
+    let cranker: Keypair;
+    let expiresAt: number;
+
+    const crankBuilder = () =>
+      program.methods
+        .crankExpiredListing()
+        .accountsPartial({
+          nftMint: coupon.mint,
+          listing: listingFor(coupon.mint, otherWallet.publicKey),
+          resaleEscrow: resaleEscrowFor(coupon.mint, otherWallet.publicKey),
+          sellerTokenAccount: ata(coupon.mint, otherWallet.publicKey),
+          seller: otherWallet.publicKey,
+          cranker: cranker.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([cranker]);
+
+    before(async () => {
+      cranker = await newFundedWallet(1);
+      coupon = await createCoupon(merchantAuthority, { title: "Stale" });
+      await claimBuilder(coupon, otherWallet).rpc();
+      expiresAt = (await chainNow()) + 5;
+      await listBuilder(coupon, otherWallet, LAMPORTS_PER_SOL, new BN(expiresAt)).rpc();
+    });
+
+    it("should reject a listing that outlives the coupon", async () => {
+      const other = await createCoupon(merchantAuthority, { title: "Stale 2" });
+      await claimBuilder(other, otherWallet).rpc();
+      const data = await program.account.couponData.fetch(other.couponData);
+      await expectError(
+        listBuilder(other, otherWallet, LAMPORTS_PER_SOL, data.expiryDate.addn(1)).rpc(),
+        "InvalidListingExpiry"
+      );
+    });
+
+    it("should refuse to crank a listing that hasn't expired", async () => {
+      await expectError(crankBuilder().rpc(), "ListingNotExpired");
+    });
+
+    it("should return the NFT and pay the cranker once expired", async () => {
+      await waitUntil(expiresAt);
+      const crankerBefore = await balance(cranker.publicKey);
+
+      await crankBuilder().rpc();
+
+      expect(await tokenBalance(ata(coupon.mint, otherWallet.publicKey))).to.equal(1);
+      // Bounty is 100_000 lamports, minus the cranker's transaction fee
+      expect(await balance(cranker.publicKey)).to.be.closeTo(
+        crankerBefore + 100_000,
+        TX_FEE_SLACK
+      );
+    });
+  });
+
+  describe("offers", () => {
+    const amount = LAMPORTS_PER_SOL / 10;
+    let coupon: Coupon;
+    let bidder: Keypair;
+
+    const placeBuilder = (expiresAt: number) =>
+      program.methods
+        .placeOffer(new BN(amount), new BN(expiresAt))
+        .accountsPartial({
+          nftMint: coupon.mint,
+          couponData: coupon.couponData,
+          merchant: coupon.merchant,
+          offer: offerFor(coupon.mint, bidder.publicKey),
+          bidder: bidder.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bidder]);
 
-      // Final redemption should burn NFT
+    const acceptBuilder = (seller: Keypair, platformWallet = feeWallet.publicKey) =>
+      program.methods
+        .acceptOffer()
+        .accountsPartial({
+          nftMint: coupon.mint,
+          couponData: coupon.couponData,
+          merchant: coupon.merchant,
+          offer: offerFor(coupon.mint, bidder.publicKey),
+          sellerTokenAccount: ata(coupon.mint, seller.publicKey),
+          bidderTokenAccount: ata(coupon.mint, bidder.publicKey),
+          bidder: bidder.publicKey,
+          seller: seller.publicKey,
+          platformConfig: platformConfigPda,
+          platformWallet,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller]);
+
+    before(async () => {
+      bidder = await newFundedWallet(5);
+      coupon = await createCoupon(merchantAuthority, { title: "Offer Me" });
+      await claimBuilder(coupon, userWallet).rpc();
+    });
+
+    it("should reject an offer that has already expired", async () => {
+      await expectError(
+        placeBuilder((await chainNow()) - 1).rpc(),
+        "InvalidOfferExpiry"
+      );
+    });
+
+    it("should lock the offer and refund it on cancel", async () => {
+      const offer = offerFor(coupon.mint, bidder.publicKey);
+      await placeBuilder((await chainNow()) + 3600).rpc();
+      const locked = await balance(offer);
+      expect(locked).to.be.greaterThan(amount);
+
+      // Only the bidder can cancel
+      await expectError(
+        program.methods
+          .cancelOffer()
+          .accountsPartial({ offer, bidder: userWallet.publicKey })
+          .signers([userWallet])
+          .rpc()
+      );
+
+      const bidderBefore = await balance(bidder.publicKey);
       await program.methods
-        .redeemCoupon()
-        .accounts({
-          couponData: multiUseCouponPda,
+        .cancelOffer()
+        .accountsPartial({ offer, bidder: bidder.publicKey })
+        .signers([bidder])
+        .rpc();
+      expect(await connection.getAccountInfo(offer)).to.be.null;
+      expect(await balance(bidder.publicKey)).to.be.closeTo(
+        bidderBefore + locked,
+        TX_FEE_SLACK
+      );
+    });
+
+    it("should refuse acceptance from a wallet without the NFT", async () => {
+      await placeBuilder((await chainNow()) + 3600).rpc();
+      await ensureAta(otherWallet, coupon.mint, otherWallet.publicKey);
+      await expectError(acceptBuilder(otherWallet).rpc(), "InvalidNFTAmount");
+    });
+
+    it("should sell the NFT to the bidder with the platform fee", async () => {
+      // The seller can't route the fee to themselves
+      await expectError(
+        acceptBuilder(userWallet, userWallet.publicKey).rpc(),
+        "InvalidFeeWallet"
+      );
+
+      const sellerBefore = await balance(userWallet.publicKey);
+      const feeBefore = await balance(feeWallet.publicKey);
+
+      await acceptBuilder(userWallet).rpc();
+
+      expect(await balance(feeWallet.publicKey)).to.equal(
+        feeBefore + platformFee(amount)
+      );
+      // Seller pays the bidder's token account rent and the transaction fee
+      expect(await balance(userWallet.publicKey)).to.be.greaterThan(
+        sellerBefore + amount - platformFee(amount) - 3_000_000
+      );
+      expect(await tokenBalance(ata(coupon.mint, bidder.publicKey))).to.equal(1);
+      expect(
+        await connection.getAccountInfo(offerFor(coupon.mint, bidder.publicKey))
+      ).to.be.null;
+    });
+  });
+
+  describe("collection offers", () => {
+    const amount = LAMPORTS_PER_SOL / 10;
+    let bidder: Keypair;
+    let coupon: Coupon;
+
+    const placeBuilder = (
+      offerId: number,
+      minDiscount: number,
+      expiresAt: number
+    ) =>
+      program.methods
+        .placeCollectionOffer(
+          new BN(offerId),
+          new BN(amount),
+          new BN(expiresAt),
+          { foodAndBeverage: {} } as any,
+          minDiscount
+        )
+        .accountsPartial({
           merchant: merchantPda,
-          nftMint: multiUseMint.publicKey,
-          nftTokenAccount: multiUseUserToken,
-          user: userWallet.publicKey,
+          collectionOffer: collectionOfferFor(merchantPda, bidder.publicKey, offerId),
+          bidder: bidder.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bidder]);
+
+    const acceptBuilder = (offerId: number, platformWallet = feeWallet.publicKey) =>
+      program.methods
+        .acceptCollectionOffer()
+        .accountsPartial({
+          nftMint: coupon.mint,
+          couponData: coupon.couponData,
+          merchant: coupon.merchant,
+          collectionOffer: collectionOfferFor(merchantPda, bidder.publicKey, offerId),
+          sellerTokenAccount: ata(coupon.mint, userWallet.publicKey),
+          bidderTokenAccount: ata(coupon.mint, bidder.publicKey),
+          bidder: bidder.publicKey,
+          seller: userWallet.publicKey,
+          platformConfig: platformConfigPda,
+          platformWallet,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([userWallet]);
+
+    before(async () => {
+      bidder = await newFundedWallet(5);
+      coupon = await createCoupon(merchantAuthority, { title: "Floor Bid" }); // 50% off
+      await claimBuilder(coupon, userWallet).rpc();
+    });
+
+    it("should reject a minimum discount above 100%", async () => {
+      await expectError(
+        placeBuilder(1, 101, (await chainNow()) + 3600).rpc(),
+        "InvalidDiscount"
+      );
+    });
+
+    it("should refuse coupons that don't meet the offer's terms", async () => {
+      await placeBuilder(2, 90, (await chainNow()) + 3600).rpc();
+      await expectError(acceptBuilder(2).rpc(), "OfferTermsNotMet");
+    });
+
+    it("should refund a cancelled collection offer", async () => {
+      const offer = collectionOfferFor(merchantPda, bidder.publicKey, 2);
+      const locked = await balance(offer);
+      const bidderBefore = await balance(bidder.publicKey);
+      await program.methods
+        .cancelCollectionOffer()
+        .accountsPartial({ collectionOffer: offer, bidder: bidder.publicKey })
+        .signers([bidder])
+        .rpc();
+      expect(await balance(bidder.publicKey)).to.be.closeTo(
+        bidderBefore + locked,
+        TX_FEE_SLACK
+      );
+
+      await expectError(
+        program.methods
+          .cancelCollectionOffer()
+          .accountsPartial({ collectionOffer: offer, bidder: bidder.publicKey })
+          .signers([bidder])
+          .rpc()
+      );
+    });
+
+    it("should fill a matching collection offer with the platform fee", async () => {
+      await placeBuilder(3, 20, (await chainNow()) + 3600).rpc();
+      await expectError(
+        acceptBuilder(3, userWallet.publicKey).rpc(),
+        "InvalidFeeWallet"
+      );
+      const feeBefore = await balance(feeWallet.publicKey);
+
+      await acceptBuilder(3).rpc();
+
+      expect(await balance(feeWallet.publicKey)).to.equal(
+        feeBefore + platformFee(amount)
+      );
+      expect(await tokenBalance(ata(coupon.mint, bidder.publicKey))).to.equal(1);
+    });
+  });
+
+  describe("auctions", () => {
+    const reserve = LAMPORTS_PER_SOL / 10;
+    const increment = LAMPORTS_PER_SOL / 100;
+    let bidderOne: Keypair;
+    let bidderTwo: Keypair;
+    let coupon: Coupon;
+    let couponExpiry: number;
+
+    const createAuctionBuilder = (endTime: number) =>
+      program.methods
+        .createAuction(new BN(reserve), new BN(endTime), new BN(increment))
+        .accountsPartial({
+          nftMint: coupon.mint,
+          couponData: coupon.couponData,
+          merchant: coupon.merchant,
+          auction: auctionFor(coupon.mint),
+          auctionEscrow: auctionEscrowFor(coupon.mint),
+          sellerTokenAccount: ata(coupon.mint, userWallet.publicKey),
+          seller: userWallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([userWallet]);
+
+    const bidBuilder = (
+      bidder: Keypair,
+      amount: number,
+      previousBidder: PublicKey | null
+    ) =>
+      program.methods
+        .placeBid(new BN(amount))
+        .accountsPartial({
+          auction: auctionFor(coupon.mint),
+          merchant: coupon.merchant,
+          previousBidder,
+          bidder: bidder.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bidder]);
+
+    const settleBuilder = (
+      recipientTokenAccount: PublicKey,
+      platformWallet = feeWallet.publicKey
+    ) =>
+      program.methods.settleAuction().accountsPartial({
+        auction: auctionFor(coupon.mint),
+        auctionEscrow: auctionEscrowFor(coupon.mint),
+        nftMint: coupon.mint,
+        couponData: coupon.couponData,
+        merchant: coupon.merchant,
+        recipientTokenAccount,
+        seller: userWallet.publicKey,
+        platformConfig: platformConfigPda,
+        platformWallet,
+        revenueVault: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      });
+
+    before(async () => {
+      bidderOne = await newFundedWallet(5);
+      bidderTwo = await newFundedWallet(5);
+      // Short-lived coupon so the capped anti-snipe extension ends the auction quickly
+      couponExpiry = (await chainNow()) + 40;
+      coupon = await createCoupon(merchantAuthority, {
+        title: "Hotel Night",
+        expiryDate: new BN(couponExpiry),
+      });
+      await claimBuilder(coupon, userWallet).rpc();
+    });
+
+    it("should reject an auction ending at or after coupon expiry", async () => {
+      await expectError(createAuctionBuilder(couponExpiry).rpc(), "InvalidAuction");
+    });
+
+    it("should escrow the NFT when the auction is created", async () => {
+      await createAuctionBuilder(couponExpiry - 30).rpc();
+      expect(await tokenBalance(auctionEscrowFor(coupon.mint))).to.equal(1);
+      const auction = await program.account.auction.fetch(auctionFor(coupon.mint));
+      expect(auction.couponExpiry.toNumber()).to.equal(couponExpiry);
+    });
+
+    it("should reject bids below the reserve", async () => {
+      await expectError(bidBuilder(bidderOne, reserve - 1, null).rpc(), "BidTooLow");
+    });
+
+    it("should refund the outbid bidder and cap the extension at coupon expiry", async () => {
+      await bidBuilder(bidderOne, reserve, null).rpc();
+
+      // Outbidding must refund the previous top bidder in full
+      await expectError(
+        bidBuilder(bidderTwo, reserve + increment, bidderTwo.publicKey).rpc(),
+        "InvalidPreviousBidder"
+      );
+      const bidderOneBefore = await balance(bidderOne.publicKey);
+      await bidBuilder(bidderTwo, reserve + increment, bidderOne.publicKey).rpc();
+      expect(await balance(bidderOne.publicKey)).to.equal(bidderOneBefore + reserve);
+
+      // Late bid: extended, but never up to the coupon's expiry
+      const auction = await program.account.auction.fetch(auctionFor(coupon.mint));
+      expect(auction.highestBidder.toString()).to.equal(bidderTwo.publicKey.toString());
+      expect(auction.endTime.toNumber()).to.be.lessThan(couponExpiry);
+    });
+
+    it("should refuse to settle before the auction ends", async () => {
+      const winnerToken = await ensureAta(bidderTwo, coupon.mint, bidderTwo.publicKey);
+      await expectError(settleBuilder(winnerToken).rpc(), "AuctionNotEnded");
+    });
+
+    it("should settle to the winner with the platform fee", async () => {
+      const auction = await program.account.auction.fetch(auctionFor(coupon.mint));
+      await waitUntil(auction.endTime.toNumber());
+      const winningBid = reserve + increment;
+
+      // Settling is permissionless, but the fee only goes to the configured wallet
+      await expectError(
+        settleBuilder(ata(coupon.mint, bidderTwo.publicKey), bidderOne.publicKey).rpc(),
+        "InvalidFeeWallet"
+      );
+      const sellerBefore = await balance(userWallet.publicKey);
+      const feeBefore = await balance(feeWallet.publicKey);
+
+      await settleBuilder(ata(coupon.mint, bidderTwo.publicKey)).rpc();
+
+      expect(await tokenBalance(ata(coupon.mint, bidderTwo.publicKey))).to.equal(1);
+      expect(await balance(feeWallet.publicKey)).to.equal(
+        feeBefore + platformFee(winningBid)
+      );
+      // Seller also gets the auction accounts' rent back
+      expect(await balance(userWallet.publicKey)).to.be.at.least(
+        sellerBefore + winningBid - platformFee(winningBid)
+      );
+      expect(await connection.getAccountInfo(auctionFor(coupon.mint))).to.be.null;
+    });
+  });
+
+  describe("create_primary_auction", () => {
+    let coupon: Coupon;
+
+    const primaryBuilder = (authority: Keypair, endTime: number, target = coupon) =>
+      program.methods
+        .createPrimaryAuction(
+          new BN(LAMPORTS_PER_SOL / 10),
+          new BN(endTime),
+          new BN(LAMPORTS_PER_SOL / 100)
+        )
+        .accountsPartial({
+          merchant: target.merchant,
+          couponData: target.couponData,
+          nftMint: target.mint,
+          nftEscrow: target.nftEscrow,
+          auction: auctionFor(target.mint),
+          auctionEscrow: auctionEscrowFor(target.mint),
+          authority: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority]);
+
+    before(async () => {
+      coupon = await createCoupon(merchantAuthority, { title: "Premium Primary" });
+    });
+
+    it("should refuse anyone but the merchant authority", async () => {
+      await expectError(
+        primaryBuilder(userWallet, (await chainNow()) + 3600).rpc(),
+        "UnauthorizedMerchant"
+      );
+    });
+
+    it("should keep bidding inside the coupon's sale window", async () => {
+      const windowed = await createCoupon(merchantAuthority, { title: "Windowed" });
+      const now = await chainNow();
+      await program.methods
+        .setCouponWindows(new BN(0), new BN(now + 600), new BN(0), new BN(0))
+        .accountsPartial(couponSetterAccounts(windowed, merchantAuthority))
+        .signers([merchantAuthority])
+        .rpc();
+      await expectError(
+        primaryBuilder(merchantAuthority, now + 3600, windowed).rpc(),
+        "InvalidAuction"
+      );
+
+      await program.methods
+        .setCouponWindows(new BN(now + 600), new BN(0), new BN(0), new BN(0))
+        .accountsPartial(couponSetterAccounts(windowed, merchantAuthority))
+        .signers([merchantAuthority])
+        .rpc();
+      await expectError(
+        primaryBuilder(merchantAuthority, now + 3600, windowed).rpc(),
+        "SaleNotStarted"
+      );
+    });
+
+    it("should refuse coupons restricted to an allowlist", async () => {
+      const vip = await createCoupon(merchantAuthority, { title: "VIP Auction" });
+      // Any non-zero root restricts the coupon
+      await program.methods
+        .setCouponAllowlist(Array(32).fill(1))
+        .accountsPartial(couponSetterAccounts(vip, merchantAuthority))
+        .signers([merchantAuthority])
+        .rpc();
+      await expectError(
+        primaryBuilder(merchantAuthority, (await chainNow()) + 3600, vip).rpc(),
+        "RestrictedCouponAuction"
+      );
+    });
+
+    it("should move the NFT from the escrow PDA into the auction", async () => {
+      await primaryBuilder(merchantAuthority, (await chainNow()) + 3600).rpc();
+      expect(await tokenBalance(coupon.nftEscrow)).to.equal(0);
+      expect(await tokenBalance(auctionEscrowFor(coupon.mint))).to.equal(1);
+      const auction = await program.account.auction.fetch(auctionFor(coupon.mint));
+      expect(auction.isPrimary).to.be.true;
+    });
+  });
+
+  describe("settle_auction without bids (primary)", () => {
+    let coupon: Coupon;
+    let endTime: number;
+
+    const settleBuilder = (
+      recipientTokenAccount: PublicKey,
+      platformWallet = feeWallet.publicKey
+    ) =>
+      program.methods.settleAuction().accountsPartial({
+        auction: auctionFor(coupon.mint),
+        auctionEscrow: auctionEscrowFor(coupon.mint),
+        nftMint: coupon.mint,
+        couponData: coupon.couponData,
+        merchant: coupon.merchant,
+        recipientTokenAccount,
+        seller: merchantAuthority.publicKey,
+        platformConfig: platformConfigPda,
+        platformWallet,
+        revenueVault: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      });
+
+    before(async () => {
+      coupon = await createCoupon(merchantAuthority, { title: "Nobody Bids" });
+      endTime = (await chainNow()) + 5;
+      await program.methods
+        .createPrimaryAuction(
+          new BN(LAMPORTS_PER_SOL / 10),
+          new BN(endTime),
+          new BN(LAMPORTS_PER_SOL / 100)
+        )
+        .accountsPartial({
+          merchant: coupon.merchant,
+          couponData: coupon.couponData,
+          nftMint: coupon.mint,
+          nftEscrow: coupon.nftEscrow,
+          auction: auctionFor(coupon.mint),
+          auctionEscrow: auctionEscrowFor(coupon.mint),
+          authority: merchantAuthority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([merchantAuthority])
+        .rpc();
+    });
+
+    it("should refuse close_coupon while the auction exists", async () => {
+      await expectError(
+        program.methods
+          .closeCoupon()
+          .accountsPartial({
+            merchant: coupon.merchant,
+            couponData: coupon.couponData,
+            nftMint: coupon.mint,
+            nftEscrow: coupon.nftEscrow,
+            auction: auctionFor(coupon.mint),
+            authority: merchantAuthority.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([merchantAuthority])
+          .rpc(),
+        "AuctionInProgress"
+      );
+    });
+
+    it("should refuse a merchant-owned token account other than the escrow PDA", async () => {
+      await waitUntil(endTime);
+      // The merchant PDA's ATA passes an owner check but isn't the escrow
+      const merchantAta = await ensureAta(merchantAuthority, coupon.mint, coupon.merchant, true);
+      await expectError(settleBuilder(merchantAta).rpc(), "InvalidAuctionRecipient");
+    });
+
+    it("should return the unsold NFT to the escrow PDA", async () => {
+      await settleBuilder(coupon.nftEscrow).rpc();
+      expect(await tokenBalance(coupon.nftEscrow)).to.equal(1);
+      expect(await connection.getAccountInfo(auctionFor(coupon.mint))).to.be.null;
+      expect(await connection.getAccountInfo(auctionEscrowFor(coupon.mint))).to.be.null;
+    });
+  });
+
+  // Merchant with deferred settlement: sale proceeds wait in its RevenueVault
+  async function initializeDeferredMerchant(name: string, holdbackSecs = 86400) {
+    const authority = await newFundedWallet(10);
+    const merchant = await initializeMerchant(authority, name);
+    const revenueVault = revenueVaultFor(merchant);
+    await program.methods
+      .configureSettlement(true, new BN(holdbackSecs))
+      .accountsPartial({
+        merchant,
+        revenueVault,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    return { authority, merchant, revenueVault };
+  }
+
+  function withdrawBuilder(
+    authority: Keypair,
+    merchant: PublicKey,
+    coupons: PublicKey[],
+    platformWallet = feeWallet.publicKey
+  ) {
+    return program.methods
+      .withdrawRevenue()
+      .accountsPartial({
+        merchant,
+        revenueVault: revenueVaultFor(merchant),
+        platformConfig: platformConfigPda,
+        authority: authority.publicKey,
+        platformWallet,
+      })
+      .remainingAccounts(
+        coupons.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }))
+      )
+      .signers([authority]);
+  }
+
+  describe("configure_settlement / withdraw_revenue", () => {
+    const price = LAMPORTS_PER_SOL / 10;
+    let deferredAuthority: Keypair;
+    let deferredMerchant: PublicKey;
+    let revenueVault: PublicKey;
+
+    before(async () => {
+      ({
+        authority: deferredAuthority,
+        merchant: deferredMerchant,
+        revenueVault,
+      } = await initializeDeferredMerchant("Deferred Diner"));
+    });
+
+    it("should reject a negative holdback", async () => {
+      await expectError(
+        program.methods
+          .configureSettlement(true, new BN(-1))
+          .accountsPartial({
+            merchant: deferredMerchant,
+            revenueVault,
+            authority: deferredAuthority.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([deferredAuthority])
+          .rpc(),
+        "InvalidHoldback"
+      );
+    });
+
+    it("should hold purchase proceeds in the revenue vault", async () => {
+      const coupon = await createCoupon(deferredAuthority, { price });
+      const vaultBefore = await balance(revenueVault);
+      await purchaseBuilder(coupon, userWallet, deferredAuthority.publicKey, price, true).rpc();
+
+      expect(await balance(revenueVault)).to.equal(vaultBefore + price);
+      const data = await program.account.couponData.fetch(coupon.couponData);
+      expect(data.escrowedLamports.toNumber()).to.equal(price);
+
+      // Still inside the holdback: nothing to withdraw
+      await expectError(
+        withdrawBuilder(deferredAuthority, deferredMerchant, [coupon.couponData]).rpc(),
+        "NothingToWithdraw"
+      );
+    });
+
+    it("should only pay platform fees to the configured fee wallet", async () => {
+      const coupon = await createCoupon(deferredAuthority, { price });
+      await purchaseBuilder(coupon, otherWallet, deferredAuthority.publicKey, price, true).rpc();
+      await redeemBuilder(coupon, otherWallet).rpc();
+
+      await expectError(
+        withdrawBuilder(
+          deferredAuthority,
+          deferredMerchant,
+          [coupon.couponData],
+          deferredAuthority.publicKey
+        ).rpc(),
+        "InvalidFeeWallet"
+      );
+
+      // Redemption released the escrow: 97.5% merchant / 2.5% platform
+      const authorityBefore = await balance(deferredAuthority.publicKey);
+      const feeBefore = await balance(feeWallet.publicKey);
+      await withdrawBuilder(deferredAuthority, deferredMerchant, [coupon.couponData]).rpc();
+      expect(await balance(feeWallet.publicKey)).to.equal(feeBefore + platformFee(price));
+      expect(await balance(deferredAuthority.publicKey)).to.be.closeTo(
+        authorityBefore + price - platformFee(price),
+        TX_FEE_SLACK
+      );
+      const vault = await program.account.revenueVault.fetch(revenueVault);
+      expect(vault.escrowedTotal.toNumber()).to.equal(price); // First coupon still held
+    });
+  });
+
+  describe("refund_coupon", () => {
+    const price = LAMPORTS_PER_SOL / 10;
+    let deferredAuthority: Keypair;
+    let deferredMerchant: PublicKey;
+    let revenueVault: PublicKey;
+
+    const refundBuilder = (coupon: Coupon, holder: Keypair) =>
+      program.methods
+        .refundCoupon()
+        .accountsPartial({
+          couponData: coupon.couponData,
+          merchant: coupon.merchant,
+          revenueVault,
+          nftMint: coupon.mint,
+          holderTokenAccount: ata(coupon.mint, holder.publicKey),
+          holder: holder.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([holder]);
+
+    before(async () => {
+      ({
+        authority: deferredAuthority,
+        merchant: deferredMerchant,
+        revenueVault,
+      } = await initializeDeferredMerchant("Refunding Diner"));
+    });
+
+    it("should refuse a refund while the coupon is active", async () => {
+      const coupon = await createCoupon(deferredAuthority, { price });
+      await purchaseBuilder(coupon, userWallet, deferredAuthority.publicKey, price, true).rpc();
+      await expectError(refundBuilder(coupon, userWallet).rpc(), "NotRefundable");
+    });
+
+    it("should refund the current holder of a cancelled coupon for good", async () => {
+      const coupon = await createCoupon(deferredAuthority, { price });
+      await purchaseBuilder(coupon, userWallet, deferredAuthority.publicKey, price, true).rpc();
+      // Resold before the merchant cancels: the new holder is owed the refund
+      await transferBuilder(coupon, userWallet, otherWallet, price).rpc();
+
+      await couponStatusBuilder(coupon, deferredAuthority, false).rpc();
+      let data = await program.account.couponData.fetch(coupon.couponData);
+      expect(data.refundOwed).to.be.true;
+
+      // Cancellation is permanent: no reactivate-withdraw-deactivate drain
+      await expectError(
+        couponStatusBuilder(coupon, deferredAuthority, true).rpc(),
+        "CouponCancelled"
+      );
+      await expectError(
+        withdrawBuilder(deferredAuthority, deferredMerchant, [coupon.couponData]).rpc(),
+        "NothingToWithdraw"
+      );
+
+      // The original purchaser no longer holds the NFT
+      await expectError(refundBuilder(coupon, userWallet).rpc());
+
+      const holderBefore = await balance(otherWallet.publicKey);
+      await refundBuilder(coupon, otherWallet).rpc();
+      expect(await balance(otherWallet.publicKey)).to.be.closeTo(
+        holderBefore + price,
+        TX_FEE_SLACK
+      );
+      expect(await tokenBalance(ata(coupon.mint, otherWallet.publicKey))).to.equal(0);
+      data = await program.account.couponData.fetch(coupon.couponData);
+      expect(data.escrowedLamports.toNumber()).to.equal(0);
+    });
+
+    it("should make the merchant fund refunds of direct-mode sales", async () => {
+      const directAuthority = await newFundedWallet(5);
+      const directMerchant = await initializeMerchant(directAuthority, "Direct Diner");
+      const directVault = revenueVaultFor(directMerchant);
+      const coupon = await createCoupon(directAuthority, { price });
+      await purchaseBuilder(coupon, userWallet, directAuthority.publicKey, price).rpc();
+      let data = await program.account.couponData.fetch(coupon.couponData);
+      expect(data.paidLamports.toNumber()).to.equal(price);
+      expect(data.escrowedLamports.toNumber()).to.equal(0);
+
+      // The merchant was paid directly: cancelling needs a vault to hold the refund
+      await expectError(
+        couponStatusBuilder(coupon, directAuthority, false).rpc(),
+        "RefundVaultRequired"
+      );
+      await program.methods
+        .configureSettlement(false, new BN(0))
+        .accountsPartial({
+          merchant: directMerchant,
+          revenueVault: directVault,
+          authority: directAuthority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([directAuthority])
+        .rpc();
+
+      const authorityBefore = await balance(directAuthority.publicKey);
+      await couponStatusBuilder(coupon, directAuthority, false, directVault).rpc();
+      expect(await balance(directAuthority.publicKey)).to.be.closeTo(
+        authorityBefore - price,
+        TX_FEE_SLACK
+      );
+      data = await program.account.couponData.fetch(coupon.couponData);
+      expect(data.refundOwed).to.be.true;
+      expect(data.escrowedLamports.toNumber()).to.equal(price);
+
+      // The buyer gets back the full price, platform fee included
+      const holderBefore = await balance(userWallet.publicKey);
+      await program.methods
+        .refundCoupon()
+        .accountsPartial({
+          couponData: coupon.couponData,
+          merchant: directMerchant,
+          revenueVault: directVault,
+          nftMint: coupon.mint,
+          holderTokenAccount: ata(coupon.mint, userWallet.publicKey),
+          holder: userWallet.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([userWallet])
         .rpc();
+      expect(await balance(userWallet.publicKey)).to.be.closeTo(
+        holderBefore + price,
+        TX_FEE_SLACK
+      );
+      const vault = await program.account.revenueVault.fetch(directVault);
+      expect(vault.escrowedTotal.toNumber()).to.equal(0);
+    });
+  });
 
-      couponData = await program.account.couponData.fetch(multiUseCouponPda);
-      expect(couponData.redemptionsRemaining).to.equal(0);
+  describe("bundles", () => {
+    const price = LAMPORTS_PER_SOL / 5;
+    let bundleCoupons: Coupon[];
+    let buyer: Keypair;
 
-      // NFT should be burned
-      try {
-        tokenBalance = await provider.connection.getTokenAccountBalance(
-          multiUseUserToken
-        );
-        expect(tokenBalance.value.uiAmount).to.equal(0);
-      } catch (error) {
-        // Account closed is also valid
-        expect(error).to.exist;
+    const groupMetas = (keys: PublicKey[]) =>
+      keys.map((pubkey, i) => ({
+        pubkey,
+        isSigner: false,
+        // CouponData and Merchant are read-only; token accounts are writable
+        isWritable: i % 4 >= 2,
+      }));
+
+    const listBundleBuilder = (bundleId: number, coupons: Coupon[]) => {
+      const bundle = bundleFor(userWallet.publicKey, bundleId);
+      return program.methods
+        .listBundle(new BN(bundleId), new BN(price))
+        .accountsPartial({
+          bundleListing: bundle,
+          seller: userWallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(
+          groupMetas(
+            coupons.flatMap((coupon) => [
+              coupon.couponData,
+              coupon.merchant,
+              ata(coupon.mint, userWallet.publicKey),
+              ata(coupon.mint, bundle, true),
+            ])
+          )
+        )
+        .signers([userWallet]);
+    };
+
+    const purchaseBundleBuilder = (
+      bundleId: number,
+      expectedPrice: number,
+      platformWallet = feeWallet.publicKey
+    ) => {
+      const bundle = bundleFor(userWallet.publicKey, bundleId);
+      return program.methods
+        .purchaseBundle(new BN(expectedPrice))
+        .accountsPartial({
+          bundleListing: bundle,
+          seller: userWallet.publicKey,
+          buyer: buyer.publicKey,
+          platformConfig: platformConfigPda,
+          platformWallet,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(
+          groupMetas(
+            bundleCoupons.flatMap((coupon) => [
+              coupon.couponData,
+              coupon.merchant,
+              ata(coupon.mint, bundle, true),
+              ata(coupon.mint, buyer.publicKey),
+            ])
+          )
+        )
+        .signers([buyer]);
+    };
+
+    const cancelBundleBuilder = (bundleId: number, seller: Keypair, coupons: Coupon[]) => {
+      const bundle = bundleFor(userWallet.publicKey, bundleId);
+      return program.methods
+        .cancelBundle()
+        .accountsPartial({
+          bundleListing: bundle,
+          seller: seller.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(
+          coupons.flatMap((coupon) => [
+            { pubkey: ata(coupon.mint, bundle, true), isSigner: false, isWritable: true },
+            {
+              pubkey: ata(coupon.mint, userWallet.publicKey),
+              isSigner: false,
+              isWritable: true,
+            },
+          ])
+        )
+        .signers([seller]);
+    };
+
+    before(async () => {
+      buyer = await newFundedWallet(5);
+      bundleCoupons = [];
+      for (const title of ["Crawl Stop 1", "Crawl Stop 2", "Crawl Stop 3"]) {
+        const coupon = await createCoupon(merchantAuthority, { title });
+        await claimBuilder(coupon, userWallet).rpc();
+        bundleCoupons.push(coupon);
+      }
+      // Bundle escrows are the bundle PDA's token accounts, created beforehand
+      for (const bundleId of [1, 2, 3]) {
+        for (const coupon of bundleCoupons) {
+          await ensureAta(userWallet, coupon.mint, bundleFor(userWallet.publicKey, bundleId), true);
+        }
+      }
+    });
+
+    it("should reject a single-coupon bundle", async () => {
+      await expectError(
+        listBundleBuilder(1, bundleCoupons.slice(0, 1)).rpc(),
+        "InvalidBundleSize"
+      );
+    });
+
+    it("should list and cancel a bundle (seller only)", async () => {
+      await listBundleBuilder(2, bundleCoupons).rpc();
+      for (const coupon of bundleCoupons) {
+        expect(
+          await tokenBalance(ata(coupon.mint, bundleFor(userWallet.publicKey, 2), true))
+        ).to.equal(1);
+      }
+
+      await expectError(cancelBundleBuilder(2, otherWallet, bundleCoupons).rpc());
+
+      await cancelBundleBuilder(2, userWallet, bundleCoupons).rpc();
+      for (const coupon of bundleCoupons) {
+        expect(await tokenBalance(ata(coupon.mint, userWallet.publicKey))).to.equal(1);
+      }
+      expect(
+        await connection.getAccountInfo(bundleFor(userWallet.publicKey, 2))
+      ).to.be.null;
+    });
+
+    it("should sell the whole bundle atomically with one platform fee", async () => {
+      await listBundleBuilder(3, bundleCoupons).rpc();
+      for (const coupon of bundleCoupons) {
+        await ensureAta(buyer, coupon.mint, buyer.publicKey);
+      }
+
+      await expectError(
+        purchaseBundleBuilder(3, price - 1).rpc(),
+        "PriceExceedsMaximum"
+      );
+
+      // The buyer can't keep the fee by passing their own wallet
+      await expectError(
+        purchaseBundleBuilder(3, price, buyer.publicKey).rpc(),
+        "InvalidFeeWallet"
+      );
+
+      const sellerBefore = await balance(userWallet.publicKey);
+      const feeBefore = await balance(feeWallet.publicKey);
+      await purchaseBundleBuilder(3, price).rpc();
+
+      expect(await balance(feeWallet.publicKey)).to.equal(feeBefore + platformFee(price));
+      // Seller also gets the escrow and listing rent back
+      expect(await balance(userWallet.publicKey)).to.be.at.least(
+        sellerBefore + price - platformFee(price)
+      );
+      for (const coupon of bundleCoupons) {
+        expect(await tokenBalance(ata(coupon.mint, buyer.publicKey))).to.equal(1);
       }
     });
   });